@@ -94,13 +94,18 @@
 // 
 // Your puzzle answer was 3952291680.
 
+mod render;
+mod ski_map;
+
+use ski_map::{BitMap, CharMap, SkiMap};
+use std::env;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Error};
+use std::io::{self, BufRead, BufReader, Error};
 use std::time::Instant;
 
 // Function to read in lines from an input file and convert them to a Vec<String>
 fn read_input(filename: &str) -> Result<Vec<String>, Error> {
-    let file = File::open(&filename)?; // Open file or panic
+    let file = File::open(filename)?; // Open file or panic
     let br = BufReader::new(file); // Create read buffer
     let mut v = vec![]; // Initialize empty vector
 
@@ -110,70 +115,47 @@ fn read_input(filename: &str) -> Result<Vec<String>, Error> {
         v.push(line?.trim().to_string());
     }
 
-    return Ok(v); // Return data
+    Ok(v) // Return data
 }
 
-// Parse the characters from the input into a 2D Vec representing the
-// map of the ski slope
-fn parse_input(input_lines: &Vec<String>) -> Vec<Vec<char>> {
-    let width = input_lines[0].as_bytes().len();
-    let height = input_lines.len();
-
-    // Create empty Vec
-    let mut ski_map = vec![vec!['.'; width]; height];
-
-    // Fill with characters from input
-    for (row, line) in input_lines.iter().enumerate() {
-        for (col, c) in line.chars().enumerate() {
-            ski_map[row][col] = c;
-        }
-    }
-
-    ski_map
+// Read the input file straight into a bit-packed map, one line at a time
+fn read_bitmap(filename: &str) -> Result<BitMap, Error> {
+    let file = File::open(filename)?;
+    BitMap::from_reader(BufReader::new(file))
 }
 
-// Given the `ski_map` and a `slope`, count the number of trees encountered
-fn trees_on_slope(ski_map: &Vec<Vec<char>>, slope: (usize, usize)) -> i64 {
-    let map_width = ski_map[0].len();
-    let mut pos = (0, 0);
-    let mut tree_count = 0;
+// The slopes to check for part two, as (down, right)
+const PART_TWO_SLOPES: [(usize, usize); 5] = [(1, 1), (1, 3), (1, 5), (1, 7), (2, 1)];
 
-    // Until the 'row' part of our current position is at the bottom of the map
-    while pos.0 < ski_map.len() {
-        if ski_map[pos.0][pos.1] == '#' {
-            tree_count += 1;
-        }
-        pos.0 += slope.0;
-        pos.1 += slope.1;
-        if pos.1 >= map_width {
-            pos.1 -= map_width;
-        }
+// Parse a "down,right" command line argument into a slope
+fn parse_slope(arg: &str) -> (usize, usize) {
+    let parts: Vec<usize> = arg.split(',').filter_map(|x| x.trim().parse().ok()).collect();
+    match parts[..] {
+        [down, right] if down > 0 => (down, right),
+        _ => panic!("Could not parse a slope from '{}', expected 'down,right'", arg),
     }
-
-    tree_count
 }
 
 // Count the trees encountered in part one, using the given slope
-fn part_one(ski_map: &Vec<Vec<char>>) {
-    let trees_found = trees_on_slope(&ski_map, (1, 3));
+fn part_one(ski_map: &dyn SkiMap) {
+    let trees_found = ski_map.trees_on_slope((1, 3));
     println!("\nThe answer to part one is {}.", trees_found);
 }
 
 // Find the answer to part two, using the list of slopes
-fn part_two(ski_map: &Vec<Vec<char>>) {
-    let slopes = [(1, 1), (1, 3), (1, 5), (1, 7), (2, 1)];
-    let part_two_total = slopes.iter()
-        .map(|x| trees_on_slope(&ski_map, *x))
-        .fold(1, |total, next| total * next);
+fn part_two(ski_map: &dyn SkiMap) {
+    let part_two_total: i64 = PART_TWO_SLOPES.iter()
+        .map(|x| ski_map.trees_on_slope(*x))
+        .product();
     println!("\nThe answer to part two is {}.", part_two_total);
 }
 
 // Timing function, given the function to run and the input arguments, runs
 // the function with the given input and prints the time to run to the
 // console
-fn time_it(f: fn(&Vec<Vec<char>>) -> (), ski_map: &Vec<Vec<char>>) {
+fn time_it(f: fn(&dyn SkiMap), ski_map: &dyn SkiMap) {
     let start = Instant::now();
-    f(&ski_map);
+    f(ski_map);
     let duration = start.elapsed();
 
     println!("Solved in: {:?}\n", duration);
@@ -181,16 +163,51 @@ fn time_it(f: fn(&Vec<Vec<char>>) -> (), ski_map: &Vec<Vec<char>>) {
 
 
 fn main() {
-    // Read in input data
-    let input_data = match read_input("../input.txt") {
-        Ok(data) => data,
-        Err(_) => panic!("Could not read input file."),
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Pass `--bitmap` to use the bit-packed map layout, which is much smaller
+    // for very long maps
+    let use_bitmap = args.iter().any(|arg| arg == "--bitmap");
+
+    // Read in input data and parse it to the chosen map layout
+    let ski_map: Box<dyn SkiMap> = if use_bitmap {
+        match read_bitmap("../input.txt") {
+            Ok(map) => Box::new(map),
+            Err(e) => panic!("Could not read input file: {}", e),
+        }
+    } else {
+        let input_data = match read_input("../input.txt") {
+            Ok(data) => data,
+            Err(_) => panic!("Could not read input file."),
+        };
+        Box::new(CharMap::from_lines(&input_data))
     };
 
-    // Parse input data to a 2D Vec
-    let ski_map = parse_input(&input_data);
+    // Pass `--render` to draw the map with the path taken down each slope
+    // marked instead of solving, either to the console or to the file named
+    // after it. Slopes are given as `--slope down,right`, and default to the
+    // slopes from part two.
+    if let Some(pos) = args.iter().position(|arg| arg == "--render") {
+        let mut slopes: Vec<(usize, usize)> = args
+            .windows(2)
+            .filter(|pair| pair[0] == "--slope")
+            .map(|pair| parse_slope(&pair[1]))
+            .collect();
+        if slopes.is_empty() {
+            slopes = PART_TWO_SLOPES.to_vec();
+        }
+
+        let result = match args.get(pos + 1).filter(|arg| !arg.starts_with("--")) {
+            Some(filename) => File::create(filename)
+                .and_then(|mut file| render::write_paths(ski_map.as_ref(), &slopes, &mut file)),
+            None => render::write_paths(ski_map.as_ref(), &slopes, &mut io::stdout().lock()),
+        };
+        if let Err(e) = result {
+            panic!("Could not render the map: {}", e);
+        }
+        return;
+    }
 
-    
-    time_it(part_one, &ski_map); // Part One
-    time_it(part_two, &ski_map); // Part Two
+    time_it(part_one, ski_map.as_ref()); // Part One
+    time_it(part_two, ski_map.as_ref()); // Part Two
 }
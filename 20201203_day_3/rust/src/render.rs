@@ -0,0 +1,68 @@
+// This module draws the ski map repeated out to the right, with the squares
+// visited along one or more slopes marked, like the example in the puzzle.
+
+use crate::ski_map::SkiMap;
+use std::io::{Error, Write};
+
+// Markers for each slope as (open square, tree). The first slope uses the
+// puzzle's own `O`/`X`, later slopes cycle through the rest of the list.
+const MARKERS: [(char, char); 5] = [('O', 'X'), ('o', 'x'), ('@', '%'), ('+', '*'), ('~', '&')];
+
+// Get the markers to use for the slope at `index` in the list of slopes
+fn markers(index: usize) -> (char, char) {
+    MARKERS[index % MARKERS.len()]
+}
+
+// Number of times the map pattern needs to be repeated to the right so that
+// every slope stays on the drawn map all the way to the bottom
+fn repeats_needed(ski_map: &dyn SkiMap, slopes: &[(usize, usize)]) -> usize {
+    let last_row = ski_map.height().saturating_sub(1);
+    let furthest_col = slopes
+        .iter()
+        .map(|(down, right)| (last_row / down) * right)
+        .max()
+        .unwrap_or(0);
+
+    furthest_col / ski_map.width() + 1
+}
+
+// Write the map to `out`, one line per row, with the squares visited along each
+// of the `slopes` (as (down, right)) marked with that slope's markers. Where two
+// slopes visit the same square, the one listed first wins. A legend of the
+// markers used for each slope is written above the map.
+pub fn write_paths<W: Write>(
+    ski_map: &dyn SkiMap,
+    slopes: &[(usize, usize)],
+    out: &mut W,
+) -> Result<(), Error> {
+    for (i, (down, right)) in slopes.iter().enumerate() {
+        let (open, tree) = markers(i);
+        writeln!(out, "Right {}, down {}: {} = open, {} = tree", right, down, open, tree)?;
+    }
+    writeln!(out)?;
+
+    let width = ski_map.width();
+    let total_width = width * repeats_needed(ski_map, slopes);
+
+    for row in 0..ski_map.height() {
+        // Draw the plain repeated row first...
+        let mut cells: Vec<char> = (0..total_width)
+            .map(|col| if ski_map.is_tree(row, col) { '#' } else { '.' })
+            .collect();
+
+        // ...then mark the square for each slope that lands on this row, going
+        // backwards so that earlier slopes overwrite later ones
+        for (i, (down, right)) in slopes.iter().enumerate().rev() {
+            if row % down != 0 {
+                continue;
+            }
+            let col = (row / down) * right;
+            let (open, tree) = markers(i);
+            cells[col] = if ski_map.is_tree(row, col) { tree } else { open };
+        }
+
+        writeln!(out, "{}", cells.into_iter().collect::<String>())?;
+    }
+
+    Ok(())
+}
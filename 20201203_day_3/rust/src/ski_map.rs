@@ -0,0 +1,171 @@
+// This module contains the representations of the ski slope map. Both layouts
+// implement the `SkiMap` trait, so the traversal code doesn't need to know
+// which one it has been handed.
+
+use std::io::{BufRead, Error, ErrorKind};
+
+// Common interface for the ski map layouts. Columns wrap around to the left
+// edge, since the pattern repeats to the right as far as it needs to.
+pub trait SkiMap {
+    // Number of columns in one repetition of the pattern
+    fn width(&self) -> usize;
+
+    // Number of rows in the map
+    fn height(&self) -> usize;
+
+    // Is there a tree at the given `row` and `col`?
+    fn is_tree(&self, row: usize, col: usize) -> bool;
+
+    // Given a `slope` as (down, right), count the number of trees encountered
+    fn trees_on_slope(&self, slope: (usize, usize)) -> i64 {
+        let map_width = self.width();
+        let mut pos = (0, 0);
+        let mut tree_count = 0;
+
+        // Until the 'row' part of our current position is at the bottom of the map
+        while pos.0 < self.height() {
+            if self.is_tree(pos.0, pos.1) {
+                tree_count += 1;
+            }
+            pos.0 += slope.0;
+            pos.1 = (pos.1 + slope.1) % map_width;
+        }
+
+        tree_count
+    }
+}
+
+// One `char` per cell, exactly as it appears in the input
+pub struct CharMap {
+    cells: Vec<Vec<char>>,
+}
+
+impl CharMap {
+    // Parse the characters from the input into a 2D Vec representing the
+    // map of the ski slope
+    pub fn from_lines(input_lines: &[String]) -> CharMap {
+        let width = input_lines[0].len();
+        let height = input_lines.len();
+
+        // Create empty Vec
+        let mut cells = vec![vec!['.'; width]; height];
+
+        // Fill with characters from input
+        for (row, line) in input_lines.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                cells[row][col] = c;
+            }
+        }
+
+        CharMap { cells }
+    }
+}
+
+impl SkiMap for CharMap {
+    fn width(&self) -> usize {
+        self.cells[0].len()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        self.cells[row][col % self.width()] == '#'
+    }
+}
+
+// One bit per cell, set where there is a tree. Each row is packed into
+// `words_per_row` consecutive u64's, so a map a few columns wide costs eight
+// bytes a row instead of a heap-allocated Vec<char>.
+pub struct BitMap {
+    width: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitMap {
+    // Build a BitMap one line at a time, so that the input never has to be
+    // held in memory as Strings. Every row must be as wide as the first one.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<BitMap, Error> {
+        let mut map = BitMap { width: 0, words_per_row: 0, words: Vec::new() };
+
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            map.push_row(line)?;
+        }
+
+        Ok(map)
+    }
+
+    // Pack a line of '.'/'#' characters into the end of the map. A row of the
+    // wrong width or with any other character is left out and reported as
+    // invalid data.
+    pub fn push_row(&mut self, line: &str) -> Result<(), Error> {
+        let row = self.height() + 1;
+        if let Some(c) = line.chars().find(|&c| c != '#' && c != '.') {
+            let message = format!("Row {}: unexpected character '{}' in '{}'", row, c, line);
+            return Err(Error::new(ErrorKind::InvalidData, message));
+        }
+        if self.words_per_row == 0 {
+            self.width = line.len();
+            self.words_per_row = self.width.div_ceil(64);
+        }
+        if line.len() != self.width {
+            let message = format!("Row {}: '{}' is not {} columns wide", row, line, self.width);
+            return Err(Error::new(ErrorKind::InvalidData, message));
+        }
+
+        let start = self.words.len();
+        self.words.resize(start + self.words_per_row, 0);
+        for (col, c) in line.bytes().enumerate() {
+            if c == b'#' {
+                self.words[start + col / 64] |= 1 << (col % 64);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SkiMap for BitMap {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.words.len().checked_div(self.words_per_row).unwrap_or(0)
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        let col = col % self.width;
+        let word = self.words[row * self.words_per_row + col / 64];
+        word & (1 << (col % 64)) != 0
+    }
+
+    // Same walk as the default, but accumulates hits in a 64-bit register and
+    // counts them with a popcount, instead of branching on every row
+    fn trees_on_slope(&self, slope: (usize, usize)) -> i64 {
+        let mut col = 0;
+        let mut hits: u64 = 0;
+        let mut filled = 0;
+        let mut tree_count = 0;
+
+        for row in (0..self.height()).step_by(slope.0) {
+            let word = self.words[row * self.words_per_row + col / 64];
+            hits |= ((word >> (col % 64)) & 1) << filled;
+            filled += 1;
+            if filled == 64 {
+                tree_count += hits.count_ones() as i64;
+                hits = 0;
+                filled = 0;
+            }
+            col = (col + slope.1) % self.width;
+        }
+
+        tree_count + hits.count_ones() as i64
+    }
+}
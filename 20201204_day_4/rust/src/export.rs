@@ -0,0 +1,158 @@
+// This module writes parsed passports back out, either in the batch file
+// format they were read from or as JSON/CSV. Fields are written in the order
+// the schema lists them, so that batches from different sources come out the
+// same way.
+
+use crate::passport::Passport;
+use crate::schema::Schema;
+use std::io::{Error, Write};
+
+// Value written in place of a redacted field
+const REDACTED: &str = "REDACTED";
+
+// The ways a batch of passports can be written
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    FieldPerLine,    // Batch format, one `key:value` per line
+    PassportPerLine, // Batch format, all of a passport's fields on one line
+    Json,            // An array of objects, one per passport, keeping the first of any repeated field
+    Csv,             // A header row of the schema's fields, then one row per passport
+}
+
+impl Format {
+    // Gets the format for a name given on the command line
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "fields" => Some(Format::FieldPerLine),
+            "passports" => Some(Format::PassportPerLine),
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+// Settings for writing a batch of passports
+pub struct ExportOptions<'a> {
+    pub format: Format,
+    pub schema: &'a Schema,    // Gives the field order, and decides validity
+    pub drop_invalid: bool,    // Leave out passports that fail the schema
+    pub redact: Vec<String>,   // Names of fields whose values are hidden
+}
+
+impl ExportOptions<'_> {
+    // The fields of `passport` in canonical order: the schema's fields first,
+    // then any fields the schema doesn't know about in input order. Repeated
+    // fields are kept, next to the first of their name.
+    fn ordered_fields<'p>(&self, passport: &'p Passport) -> Vec<(&'p str, &'p str)> {
+        let fields = passport.fields();
+        let rank = |name: &str| {
+            self.schema.fields.iter().position(|rule| rule.name == name).unwrap_or(usize::MAX)
+        };
+
+        let mut ordered: Vec<(usize, usize, &str, &str)> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, (name, value))| {
+                let first = fields.iter().position(|(k, _)| k == name).unwrap_or(i);
+                (rank(name), first, name.as_str(), value.as_str())
+            })
+            .collect();
+        ordered.sort_by_key(|&(rank, first, _, _)| (rank, first));
+
+        ordered
+            .into_iter()
+            .map(|(_, _, name, value)| {
+                if self.redact.iter().any(|x| x == name) { (name, REDACTED) } else { (name, value) }
+            })
+            .collect()
+    }
+}
+
+// Escapes a string for use inside a JSON string literal
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Quotes a CSV cell if it contains anything that would break the row up
+fn csv_cell(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Writes `passports` to `out` according to `options`
+pub fn write_passports<W: Write>(
+    passports: &[Passport],
+    options: &ExportOptions,
+    out: &mut W,
+) -> Result<(), Error> {
+    let kept: Vec<&Passport> = passports
+        .iter()
+        .filter(|x| !options.drop_invalid || options.schema.is_valid(x))
+        .collect();
+
+    match options.format {
+        Format::FieldPerLine | Format::PassportPerLine => {
+            let separator = if let Format::FieldPerLine = options.format { "\n" } else { " " };
+            for (i, passport) in kept.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                let mut tokens: Vec<String> = options
+                    .ordered_fields(passport)
+                    .iter()
+                    .map(|(name, value)| format!("{}:{}", name, value))
+                    .collect();
+                tokens.extend(passport.malformed().iter().cloned());
+                writeln!(out, "{}", tokens.join(separator))?;
+            }
+        }
+        Format::Json => {
+            writeln!(out, "[")?;
+            for (i, passport) in kept.iter().enumerate() {
+                let mut fields = options.ordered_fields(passport);
+                fields.dedup_by_key(|(name, _)| *name);
+                let members: Vec<String> = fields
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", json_string(name), json_string(value)))
+                    .collect();
+                let comma = if i + 1 < kept.len() { "," } else { "" };
+                writeln!(out, "  {{{}}}{}", members.join(", "), comma)?;
+            }
+            writeln!(out, "]")?;
+        }
+        Format::Csv => {
+            let header: Vec<String> = options.schema.fields.iter().map(|x| csv_cell(&x.name)).collect();
+            writeln!(out, "{}", header.join(","))?;
+            for passport in kept {
+                let fields = options.ordered_fields(passport);
+                let row: Vec<String> = options
+                    .schema
+                    .fields
+                    .iter()
+                    .map(|rule| {
+                        let value = fields.iter().find(|(name, _)| *name == rule.name);
+                        value.map_or(String::new(), |(_, value)| csv_cell(value))
+                    })
+                    .collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+        }
+    }
+
+    Ok(())
+}
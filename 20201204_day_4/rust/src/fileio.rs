@@ -4,7 +4,7 @@ use std::io::{BufRead, BufReader, Error};
 // Function to read in lines from an input file and convert them to a 
 // Vec<String>, collapses each set of fields to a single String
 pub fn read_input(filename: &str) -> Result<Vec<String>, Error> {
-    let file = File::open(&filename)?; // Open file or panic
+    let file = File::open(filename)?; // Open file or panic
     let br = BufReader::new(file); // Create read buffer
     let mut v = vec![]; // Initialize empty vector
 
@@ -23,9 +23,9 @@ pub fn read_input(filename: &str) -> Result<Vec<String>, Error> {
             i += 1;
         } else {
             v[i].push_str(&line_string);
-            v[i].push_str(" ");
+            v[i].push(' ');
         }
     }
 
-    return Ok(v); // Return data
+    Ok(v) // Return data
 }
@@ -142,25 +142,34 @@
 // 
 
 // Modules for code organization
+mod export;
 mod fileio;
 mod passport;
 mod part_one;
 mod part_two;
+mod report;
+mod schema;
 
 // Needed to use the `lazy_static` and `regex` crates
 #[macro_use] extern crate lazy_static;
 extern crate regex;
 
 // Other imports
+use export::{ExportOptions, Format};
 use passport::Passport;
+use report::BatchReport;
+use schema::{Schema, PART_TWO_SCHEMA};
+use std::env;
+use std::fs::File;
+use std::io;
 use std::time::Instant;
 
 // Timing function, given the function to run and the input arguments, runs
 // the function with the given input and prints the time to run to the
 // console
-fn time_it(f: fn(&Vec<Passport>) -> (), passports: &Vec<Passport>) {
+fn time_it(f: fn(&[Passport]), passports: &[Passport]) {
     let start = Instant::now();
-    f(&passports);
+    f(passports);
     let duration = start.elapsed();
 
     println!("Solved in: {:?}\n", duration);
@@ -168,6 +177,7 @@ fn time_it(f: fn(&Vec<Passport>) -> (), passports: &Vec<Passport>) {
 
 // Program entrypoint
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
 
     // Read in the input file to Vec<String>
     let input_lines = match fileio::read_input("../input.txt") {
@@ -180,6 +190,74 @@ fn main() {
         .map(|x| Passport::from_line(x))
         .collect();
 
+    // Pass `--schema <file>` to check the passports against the rules in that
+    // file instead of the puzzle's
+    let custom_schema = args.iter().position(|arg| arg == "--schema").map(|pos| {
+        let filename = match args.get(pos + 1) {
+            Some(x) => x,
+            None => panic!("Expected a schema file after --schema"),
+        };
+        match Schema::from_file(filename) {
+            Ok(x) => x,
+            Err(e) => panic!("Couldn't load schema: {}", e),
+        }
+    });
+
+    // Pass `--report` to list every failing field on every passport, and how
+    // often each field failed across the batch, using the part two rules
+    // unless a schema was given
+    if args.iter().any(|arg| arg == "--report") {
+        let schema = custom_schema.as_ref().unwrap_or(&PART_TWO_SCHEMA);
+        let report = BatchReport::new(schema, &passports);
+        if let Err(e) = report.write_to(&mut io::stdout().lock()) {
+            panic!("Couldn't write report: {}", e);
+        }
+        return;
+    }
+
+    // Pass `--export <fields|passports|json|csv>` to write the passports back
+    // out with their fields in schema order, to the console or to the file
+    // given with `--output <file>`. `--drop-invalid` leaves out passports that
+    // fail the schema, and `--redact pid,cid` hides the values of those fields.
+    if let Some(pos) = args.iter().position(|arg| arg == "--export") {
+        let format = match args.get(pos + 1).and_then(|x| Format::from_name(x)) {
+            Some(x) => x,
+            None => panic!("Expected one of fields, passports, json or csv after --export"),
+        };
+        let redact = match args.iter().position(|arg| arg == "--redact") {
+            Some(pos) => match args.get(pos + 1) {
+                Some(x) => x.split(',').map(|x| x.to_string()).collect(),
+                None => panic!("Expected a list of fields after --redact"),
+            },
+            None => Vec::new(),
+        };
+        let options = ExportOptions {
+            format,
+            schema: custom_schema.as_ref().unwrap_or(&PART_TWO_SCHEMA),
+            drop_invalid: args.iter().any(|arg| arg == "--drop-invalid"),
+            redact,
+        };
+
+        let output = args.iter().position(|arg| arg == "--output").and_then(|pos| args.get(pos + 1));
+        let result = match output {
+            Some(filename) => File::create(filename)
+                .and_then(|mut file| export::write_passports(&passports, &options, &mut file)),
+            None => export::write_passports(&passports, &options, &mut io::stdout().lock()),
+        };
+        if let Err(e) = result {
+            panic!("Couldn't export passports: {}", e);
+        }
+        return;
+    }
+
+    // With just a schema, count the passports that are valid according to it,
+    // instead of solving the puzzle
+    if let Some(schema) = custom_schema {
+        let valid_count = passports.iter().filter(|x| schema.is_valid(x)).count();
+        println!("\nFound {} valid passports according to the given schema.", valid_count);
+        return;
+    }
+
     // Run/time puzzle parts
     time_it(part_one::valid_passports, &passports); // 228
     time_it(part_two::valid_passports, &passports); // 175
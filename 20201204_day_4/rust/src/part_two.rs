@@ -1,11 +1,12 @@
 use crate::Passport;
+use crate::schema::PART_TWO_SCHEMA;
 
 // Validates a Vec of passports according to the Part Two rules and count
 // the number of valid passports
-pub fn valid_passports(passports: &Vec<Passport>) {
+pub fn valid_passports(passports: &[Passport]) {
     let valid_count = passports.iter()
-        .map(|x| x.part_two_valid())
-        .fold(0, |total, next| if next { total + 1 } else { total });
+        .filter(|x| PART_TWO_SCHEMA.is_valid(x))
+        .count();
 
     println!("\nFound {} valid passports, part two.", valid_count);
-}
\ No newline at end of file
+}
@@ -0,0 +1,62 @@
+// This module builds a field-by-field validation report for a batch of
+// passports: every failing field on every passport, plus how often each kind
+// of failure turned up across the whole batch.
+
+use crate::passport::Passport;
+use crate::schema::{FailureKind, FieldFailure, Schema};
+use std::collections::HashMap;
+use std::io::{Error, Write};
+
+// The validation results for a batch of passports, in input order
+pub struct BatchReport {
+    pub results: Vec<Vec<FieldFailure>>,
+}
+
+impl BatchReport {
+    // Validates every passport in the batch against `schema`
+    pub fn new(schema: &Schema, passports: &[Passport]) -> BatchReport {
+        let results = passports.iter().map(|x| schema.validate(x)).collect();
+        BatchReport { results }
+    }
+
+    // Number of passports with no failing fields
+    pub fn valid_count(&self) -> usize {
+        self.results.iter().filter(|x| x.is_empty()).count()
+    }
+
+    // Number of times each field failed in each way across the batch, most
+    // frequent first
+    pub fn failure_counts(&self) -> Vec<(String, FailureKind, usize)> {
+        let mut counts: HashMap<(&str, FailureKind), usize> = HashMap::new();
+        for failure in self.results.iter().flatten() {
+            *counts.entry((&failure.field, failure.kind)).or_insert(0) += 1;
+        }
+
+        let mut counts: Vec<(String, FailureKind, usize)> = counts
+            .into_iter()
+            .map(|((field, kind), count)| (field.to_string(), kind, count))
+            .collect();
+        counts.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| (&a.0, a.1).cmp(&(&b.0, b.1))));
+        counts
+    }
+
+    // Writes one line per invalid passport listing its failures, followed by
+    // the summary of failure frequencies
+    pub fn write_to<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        for (i, failures) in self.results.iter().enumerate() {
+            if failures.is_empty() {
+                continue;
+            }
+            let listed: Vec<String> = failures.iter().map(|x| x.to_string()).collect();
+            writeln!(out, "Passport {}: {}", i + 1, listed.join(", "))?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "{} of {} passports valid", self.valid_count(), self.results.len())?;
+        for (field, kind, count) in self.failure_counts() {
+            writeln!(out, "{:>6}  {}: {}", count, field, kind)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+// This module contains the validation rules for passports (or any other
+// document made of `key:value` fields), loaded from a schema file instead of
+// being compiled in. The rules for both parts of the puzzle are bundled in
+// `schemas/`.
+
+use crate::passport::Passport;
+use regex::Regex;
+use std::fmt;
+use std::fs;
+use std::ops::RangeInclusive;
+
+// The kind of value a field holds, along with any constraints on that value
+#[derive(Debug)]
+pub enum FieldKind {
+    // Any string, optionally required to match a pattern
+    Text(Option<Pattern>),
+
+    // A whole number, optionally required to fall within a range
+    Int(Option<RangeInclusive<i64>>),
+
+    // A whole number followed by one of the listed units, and the range the
+    // number has to fall within for that unit (e.g. "150cm" or "59in")
+    Measure(Vec<(String, RangeInclusive<i64>)>),
+}
+
+// A pattern from a schema file. The whole value has to match, so the regex is
+// compiled with anchors around the entire pattern; otherwise a pattern like
+// `amb|blu` would accept "ambxyz".
+#[derive(Debug)]
+pub struct Pattern {
+    source: String,
+    regex: Regex,
+}
+
+impl Pattern {
+    fn new(source: &str) -> Result<Pattern, regex::Error> {
+        let regex = Regex::new(&format!("^(?:{})$", source))?;
+        Ok(Pattern { source: source.to_string(), regex })
+    }
+}
+
+// The ways a single field can fail validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FailureKind {
+    Missing,      // Required, but not present
+    Duplicate,    // Present more than once
+    UnknownField, // Not in the schema at all
+    Malformed,    // Not a `key:value` pair, or not a number where one was expected
+    OutOfRange,   // A number outside the allowed range
+    UnknownUnit,  // A measurement without one of the allowed units
+    NoMatch,      // Text that doesn't match the pattern
+}
+
+// A field that failed validation, with the rule it broke and the offending
+// value, e.g. "hgt: 190in outside 59–76in"
+#[derive(Debug)]
+pub struct FieldFailure {
+    pub field: String,
+    pub kind: FailureKind,
+    pub detail: String,
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            FailureKind::Missing => "missing",
+            FailureKind::Duplicate => "duplicate",
+            FailureKind::UnknownField => "unknown field",
+            FailureKind::Malformed => "malformed",
+            FailureKind::OutOfRange => "out of range",
+            FailureKind::UnknownUnit => "unknown unit",
+            FailureKind::NoMatch => "no pattern match",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for FieldFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.detail)
+    }
+}
+
+// The rule for a single field
+#[derive(Debug)]
+pub struct FieldRule {
+    pub name: String,
+    pub required: bool,
+    pub kind: FieldKind,
+}
+
+// A full set of field rules, in the order they appear in the schema file
+#[derive(Debug)]
+pub struct Schema {
+    pub fields: Vec<FieldRule>,
+}
+
+// Parses a "min..=max" string into a range
+fn parse_range(string: &str) -> Option<RangeInclusive<i64>> {
+    let (min, max) = string.split_once("..=")?;
+    Some(min.parse().ok()?..=max.parse().ok()?)
+}
+
+// Formats a range the way it's shown in failure messages, e.g. "59–76"
+fn show_range(range: &RangeInclusive<i64>) -> String {
+    format!("{}–{}", range.start(), range.end())
+}
+
+// Parses a whole number made of nothing but digits and an optional leading
+// minus sign, unlike `str::parse`, which also allows a leading plus
+fn parse_int(value: &str) -> Option<i64> {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+// Splits a value like "150cm" into the number and the unit
+fn split_measure(value: &str) -> Option<(i64, &str)> {
+    let unit_start = value.find(|c: char| !c.is_ascii_digit())?;
+    let number = parse_int(&value[..unit_start])?;
+    Some((number, &value[unit_start..]))
+}
+
+impl FieldKind {
+    // Parses the type name and constraints from a line of a schema file
+    fn parse(kind: &str, constraints: &[&str]) -> Result<FieldKind, String> {
+        match (kind, constraints) {
+            ("text", []) => Ok(FieldKind::Text(None)),
+            ("text", [pattern]) => {
+                let pattern = pattern
+                    .strip_prefix('/')
+                    .and_then(|p| p.strip_suffix('/'))
+                    .ok_or(format!("Pattern '{}' should be wrapped in '/'", pattern))?;
+                match Pattern::new(pattern) {
+                    Ok(re) => Ok(FieldKind::Text(Some(re))),
+                    Err(e) => Err(format!("Invalid pattern '{}': {}", pattern, e)),
+                }
+            }
+            ("int", []) => Ok(FieldKind::Int(None)),
+            ("int", [range]) => match parse_range(range) {
+                Some(range) => Ok(FieldKind::Int(Some(range))),
+                None => Err(format!("Invalid range '{}', expected 'min..=max'", range)),
+            },
+            ("measure", units) if !units.is_empty() => {
+                let mut parsed = Vec::with_capacity(units.len());
+                for unit in units {
+                    let (name, range) = unit.split_once(':').unwrap_or(("", unit));
+                    match parse_range(range) {
+                        Some(range) if !name.is_empty() => parsed.push((name.to_string(), range)),
+                        _ => return Err(format!("Invalid unit '{}', expected 'unit:min..=max'", unit)),
+                    }
+                }
+                Ok(FieldKind::Measure(parsed))
+            }
+            ("text", _) | ("int", _) | ("measure", _) => {
+                Err(format!("Wrong number of constraints for type '{}'", kind))
+            }
+            _ => Err(format!("Unknown field type '{}'", kind)),
+        }
+    }
+
+    // Checks whether `value` is allowed for this kind of field, and if not,
+    // describes why not
+    pub fn check(&self, value: &str) -> Result<(), (FailureKind, String)> {
+        match self {
+            FieldKind::Text(None) => Ok(()),
+            FieldKind::Text(Some(pattern)) => {
+                if pattern.regex.is_match(value) {
+                    Ok(())
+                } else {
+                    Err((FailureKind::NoMatch, format!("{} doesn't match {}", value, pattern.source)))
+                }
+            }
+            FieldKind::Int(range) => {
+                let x = match parse_int(value) {
+                    Some(x) => x,
+                    None => {
+                        return Err((FailureKind::Malformed, format!("{} is not a whole number", value)))
+                    }
+                };
+                match range {
+                    Some(r) if !r.contains(&x) => {
+                        Err((FailureKind::OutOfRange, format!("{} outside {}", value, show_range(r))))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            FieldKind::Measure(units) => {
+                let (x, unit) = match split_measure(value) {
+                    Some(x) => x,
+                    None => {
+                        let names: Vec<&str> = units.iter().map(|(u, _)| u.as_str()).collect();
+                        let detail = format!("{} is not a measurement in {}", value, names.join("/"));
+                        return Err((FailureKind::UnknownUnit, detail));
+                    }
+                };
+                match units.iter().find(|(u, _)| u == unit) {
+                    Some((_, r)) if r.contains(&x) => Ok(()),
+                    Some((u, r)) => {
+                        Err((FailureKind::OutOfRange, format!("{} outside {}{}", value, show_range(r), u)))
+                    }
+                    None => {
+                        let names: Vec<&str> = units.iter().map(|(u, _)| u.as_str()).collect();
+                        let detail = format!("{} has unit '{}', expected {}", value, unit, names.join("/"));
+                        Err((FailureKind::UnknownUnit, detail))
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The bundled rule sets. This prevents compiling the regular expressions on
+// each loop, vastly increasing performance.
+lazy_static! {
+    pub static ref PART_ONE_SCHEMA: Schema =
+        Schema::parse(include_str!("../schemas/part_one.schema")).unwrap();
+    pub static ref PART_TWO_SCHEMA: Schema =
+        Schema::parse(include_str!("../schemas/part_two.schema")).unwrap();
+}
+
+impl Schema {
+    // Parses the contents of a schema file. Each non-blank, non-comment line is
+    // `<field> <required|optional> <type> [constraints...]`.
+    pub fn parse(source: &str) -> Result<Schema, String> {
+        let mut fields = Vec::new();
+
+        for (line_no, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let rule = match parts[..] {
+                [name, required, kind, ref constraints @ ..] => {
+                    let required = match required {
+                        "required" => true,
+                        "optional" => false,
+                        _ => return Err(format!("Line {}: expected 'required' or 'optional'", line_no + 1)),
+                    };
+                    let kind = FieldKind::parse(kind, constraints)
+                        .map_err(|e| format!("Line {}: {}", line_no + 1, e))?;
+                    FieldRule { name: name.to_string(), required, kind }
+                }
+                _ => return Err(format!("Line {}: expected '<field> <required|optional> <type>'", line_no + 1)),
+            };
+            fields.push(rule);
+        }
+
+        Ok(Schema { fields })
+    }
+
+    // Reads and parses a schema file from disk
+    pub fn from_file(filename: &str) -> Result<Schema, String> {
+        let source = fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+        Schema::parse(&source)
+    }
+
+    // Checks the validity of the passport according to these rules: every
+    // required field has to be present, every field that is present has to
+    // hold an acceptable value, and there can't be any malformed, unknown or
+    // repeated fields
+    pub fn is_valid(&self, passport: &Passport) -> bool {
+        self.validate(passport).is_empty()
+    }
+
+    // Checks the passport against every rule, without stopping at the first
+    // failure, and lists every field that failed. An empty list means the
+    // passport is valid.
+    pub fn validate(&self, passport: &Passport) -> Vec<FieldFailure> {
+        let mut failures = Vec::new();
+        let mut fail = |field: &str, kind: FailureKind, detail: String| {
+            failures.push(FieldFailure { field: field.to_string(), kind, detail });
+        };
+
+        for raw in passport.malformed() {
+            let field = raw.split(':').next().unwrap_or(raw);
+            fail(field, FailureKind::Malformed, format!("'{}' is not a key:value pair", raw));
+        }
+
+        for (i, (name, _)) in passport.fields().iter().enumerate() {
+            if !self.fields.iter().any(|rule| &rule.name == name) {
+                fail(name, FailureKind::UnknownField, String::from("unknown field"));
+            }
+
+            // Only report a repeated field at its first appearance
+            let earlier = passport.fields()[..i].iter().any(|(k, _)| k == name);
+            let count = passport.fields().iter().filter(|(k, _)| k == name).count();
+            if !earlier && count > 1 {
+                fail(name, FailureKind::Duplicate, format!("appears {} times", count));
+            }
+        }
+
+        for rule in &self.fields {
+            let failure = match passport.get(&rule.name) {
+                Some(value) => rule.kind.check(value).err(),
+                None if rule.required => Some((FailureKind::Missing, String::from("missing"))),
+                None => None,
+            };
+            if let Some((kind, detail)) = failure {
+                fail(&rule.name, kind, detail);
+            }
+        }
+
+        failures
+    }
+}
@@ -1,64 +1,121 @@
-use std::ops::Range;
+use crate::seat_layout::SeatLayout;
+use std::fmt;
 
-// Struct to represent a boarding pass, contains field for the row indicators and seat indicators
+// Struct to represent a boarding pass, contains the characters from the pass, along with the
+// layout of the aircraft the pass is for
 #[derive(Debug)]
 pub struct BoardingPass {
-    row_array: [char; 7],
-    seat_array: [char; 3],
+    code: String,
+    layout: SeatLayout,
 }
 
 // Methods for a boarding pass struct
 impl BoardingPass {
-    // Parses a line from the input file into a BoardingPass
-    pub fn from_string(pass_str: &str) -> BoardingPass {
-        let mut row_array = ['X'; 7];
-        for (i, c) in pass_str[..7].chars().enumerate() {
-            row_array[i] = c;
-        }
+    // Parses a line from the input file into a BoardingPass for an aircraft with the given
+    // `layout`. The characters aren't checked until the pass is decoded.
+    pub fn from_string(pass_str: &str, layout: SeatLayout) -> BoardingPass {
+        BoardingPass{code: pass_str.to_string(), layout}
+    }
 
-        let mut seat_array = ['X'; 3];
-        for (i, c) in pass_str[7..10].chars().enumerate() {
-            seat_array[i] = c;
+    // Creates the BoardingPass for the seat with the given `seat_id`, the inverse of
+    // `seat_number()`
+    pub fn from_seat_id(seat_id: u32, layout: SeatLayout) -> Result<BoardingPass, String> {
+        if u64::from(seat_id) >= layout.seat_count() {
+            return Err(format!("Seat ID {} is too large, the layout has {} seats", seat_id, layout.seat_count()));
         }
 
-        BoardingPass{row_array, seat_array}
+        // The row characters are the high bits of the seat ID, the column characters are the low
+        // bits, each written most significant bit first
+        let bits = layout.row_bits + layout.col_bits;
+        let code = (0..bits)
+            .map(|i| {
+                let bit = (seat_id >> (bits - 1 - i)) & 1;
+                let letters = if i < layout.row_bits { layout.row_letters } else { layout.col_letters };
+                if bit == 1 { letters.1 } else { letters.0 }
+            })
+            .collect();
+
+        Ok(BoardingPass{code, layout})
     }
 
-    // Calculates a seat number for a BoardingPass
-    pub fn seat_number(&self) -> u32 {
-        let mut row_range: Range<u32> = 0..127;  // The available range of rows
-        let mut seat_range: Range<u32> = 0..7;   // The available range of seats
-
-        // For each element of the BoardingPass.row_array, divide the available rows in half based
-        // on the puzzle directions ('F' for lower half, 'B' for upper half). Once it's narrowed
-        // down to the end, there should be only one row left.
-        for c in self.row_array.iter() {
-            let halfway = ((row_range.end - row_range.start)/2) + row_range.start;
-            row_range = match c {
-                'B' => (halfway+1)..row_range.end,
-                'F' => row_range.start..halfway,
-                _ => panic!("{} not a valid row indicator", c),
-            }
-        }
-        if row_range.start != row_range.end {
-            panic!("Failed to find row from {:?}", self.row_array);
+    // Calculates a seat number for a BoardingPass. Picking the upper or lower half of the
+    // remaining rows (or columns) with each character is the same as reading the characters as
+    // the bits of a binary number, so the seat number is built up one bit at a time.
+    pub fn seat_number(&self) -> Result<u32, String> {
+        let layout = &self.layout;
+        if self.code.chars().count() != layout.pass_len() {
+            return Err(format!("{} should be {} characters long", self.code, layout.pass_len()));
         }
 
-        // For each element of the BoardingPass.seat_array, divide the available seats in half 
-        // based on the puzzle directions ('L' for lower half, 'R' for upper half). Once it's 
-        // narrowed down to the end, there should be only one seat left.
-        for c in self.seat_array.iter() {
-            let halfway = ((seat_range.end - seat_range.start)/2) + seat_range.start;
-            seat_range = match c {
-                'R' => (halfway+1)..seat_range.end,
-                'L' => seat_range.start..halfway,
-                _ => panic!("{} not a valid seat indicator", c),
-            }
+        let mut seat_number = 0;
+        for (i, c) in self.code.chars().enumerate() {
+            let letters = if i < layout.row_bits as usize { layout.row_letters } else { layout.col_letters };
+            let bit = match c {
+                x if x == letters.0 => 0,
+                x if x == letters.1 => 1,
+                _ => return Err(format!(
+                    "{} not a valid indicator at position {} of {}, expected {} or {}",
+                    c, i, self.code, letters.0, letters.1
+                )),
+            };
+            seat_number = (seat_number << 1) | bit;
         }
-        if seat_range.start != seat_range.end {
-            panic!("Failed to find seat from {:?}", self.seat_array);
+
+        Ok(seat_number)
+    }
+}
+
+// Prints the characters on the pass, e.g. "FBFBBFFRLR"
+impl fmt::Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Encoding every seat ID for the `layout` into a BoardingPass and decoding it again should give
+    // back the same seat ID
+    fn assert_round_trip(layout: SeatLayout) {
+        for seat_id in 0..layout.seat_count() as u32 {
+            let pass = BoardingPass::from_seat_id(seat_id, layout).unwrap();
+            assert_eq!(pass.seat_number(), Ok(seat_id), "seat ID {} encoded as {}", seat_id, pass);
         }
+    }
+
+    #[test]
+    fn round_trip_default_layout() {
+        assert_round_trip(SeatLayout::default());
+    }
+
+    #[test]
+    fn round_trip_8_2_layout() {
+        assert_round_trip(SeatLayout::from_spec("8,2").unwrap());
+    }
+
+    #[test]
+    fn decodes_puzzle_example() {
+        let pass = BoardingPass::from_string("FBFBBFFRLR", SeatLayout::default());
+        assert_eq!(pass.seat_number(), Ok(357));
+    }
+
+    #[test]
+    fn invalid_character_is_an_error() {
+        let pass = BoardingPass::from_string("FBFBBFFRLX", SeatLayout::default());
+        assert!(pass.seat_number().is_err());
+    }
 
-        (row_range.start * 8) + seat_range.start  // Calculate and return seat number
+    #[test]
+    fn wrong_length_is_an_error() {
+        let layout = SeatLayout::default();
+        assert!(BoardingPass::from_string("FBFBBFFRL", layout).seat_number().is_err());
+        assert!(BoardingPass::from_string("FBFBBFFRLRR", layout).seat_number().is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn seat_id_too_large_is_an_error() {
+        assert!(BoardingPass::from_seat_id(1024, SeatLayout::default()).is_err());
+    }
+}
@@ -72,12 +72,18 @@
 
 mod fileio;
 mod boarding_pass;
+mod occupancy;
+mod seat_layout;
 
 use boarding_pass::BoardingPass;
+use occupancy::Occupancy;
+use seat_layout::SeatLayout;
+use std::env;
+use std::io;
 use std::time::Instant;
 
 // Part One, find the highest seat number in the Vec of seat numbers
-fn part_one(seat_numbers: &Vec<u32>) {
+fn part_one(seat_numbers: &[u32]) {
     let max_seat_number = seat_numbers.iter()
         .fold(0, |max, next| if next > &max { *next } else { max });
         
@@ -85,7 +91,7 @@ fn part_one(seat_numbers: &Vec<u32>) {
 }
 
 // Part Two, find the missing seat number in the Vec of seat numbers.
-fn part_two(seat_numbers: &Vec<u32>) {
+fn part_two(seat_numbers: &[u32]) {
     let mut missing_seat_number = 0;
     for (i, n) in seat_numbers.iter().enumerate() {
         if i == 0 || i == seat_numbers.len() - 1 { continue; }
@@ -103,16 +109,46 @@ fn part_two(seat_numbers: &Vec<u32>) {
 // Timing function, given the function to run and the input arguments, runs
 // the function with the given input and prints the time to run to the
 // console
-fn time_it(f: fn(&Vec<u32>) -> (), seat_numbers: &Vec<u32>) {
+fn time_it(f: fn(&[u32]), seat_numbers: &[u32]) {
     let start = Instant::now();
-    f(&seat_numbers);
+    f(seat_numbers);
     let duration = start.elapsed();
 
     println!("Solved in: {:?}\n", duration);
 }
 
 fn main() {
-       // Read in the input file to Vec<String>
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Pass `--layout <row bits>,<column bits>[,<letters>]` for boarding passes from an aircraft
+    // other than the one in the puzzle, e.g. `--layout 8,2` or `--layout 8,2,FBLR`
+    let layout = match args.iter().position(|arg| arg == "--layout") {
+        Some(pos) => match args.get(pos + 1).and_then(|x| SeatLayout::from_spec(x)) {
+            Some(x) => x,
+            None => panic!("Expected a layout like 8,2 or 8,2,FBLR after --layout"),
+        },
+        None => SeatLayout::default(),
+    };
+
+    // Pass `--encode <seat ID>,<seat ID>,...` to print the boarding pass for each seat ID
+    if let Some(pos) = args.iter().position(|arg| arg == "--encode") {
+        let seat_ids = match args.get(pos + 1) {
+            Some(x) => x,
+            None => panic!("Expected a list of seat IDs after --encode"),
+        };
+        for seat_id in seat_ids.split(',') {
+            let pass = seat_id.trim().parse()
+                .map_err(|_| format!("{} is not a seat ID", seat_id))
+                .and_then(|x| BoardingPass::from_seat_id(x, layout));
+            match pass {
+                Ok(x) => println!("{}: {}", seat_id.trim(), x),
+                Err(e) => panic!("{}", e),
+            }
+        }
+        return;
+    }
+
+    // Read in the input file to Vec<String>
     let input_lines = match fileio::read_input("../input.txt") {
         Ok(x) => x,
         Err(_) => panic!("Couldn't parse input.")
@@ -121,13 +157,27 @@ fn main() {
     // Parse boarding passes into structured data
     let mut boarding_passes = Vec::new();
     for line in input_lines {
-        boarding_passes.push(BoardingPass::from_string(&line));
+        boarding_passes.push(BoardingPass::from_string(&line, layout));
+    }
+
+    // Pass `--report` to print every empty seat and duplicate pass, along with a chart of the
+    // whole cabin, instead of solving the puzzle
+    if args.iter().any(|arg| arg == "--report") {
+        let occupancy = match Occupancy::from_passes(layout, &boarding_passes) {
+            Ok(x) => x,
+            Err(e) => panic!("Couldn't decode boarding pass: {}", e),
+        };
+        if let Err(e) = occupancy.write_report(&mut io::stdout().lock()) {
+            panic!("Couldn't write report: {}", e);
+        }
+        return;
     }
 
     // Get the seat number for each boarding pass
-    let mut seat_numbers: Vec<u32> = boarding_passes.iter()
-        .map(|x| x.seat_number())
-        .collect();
+    let mut seat_numbers: Vec<u32> = match boarding_passes.iter().map(|x| x.seat_number()).collect() {
+        Ok(x) => x,
+        Err(e) => panic!("Couldn't decode boarding pass: {}", e),
+    };
     
     seat_numbers.sort_unstable();  // Sort seat numbers, for part two
 
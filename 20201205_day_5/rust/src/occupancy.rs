@@ -0,0 +1,169 @@
+use crate::boarding_pass::BoardingPass;
+use crate::seat_layout::SeatLayout;
+use std::collections::BTreeMap;
+use std::io::{Error, Write};
+
+// Why a seat has no boarding pass. Seats before the first occupied seat or after the last one
+// are taken to be missing from the very front or back of the plane; anything in between is a
+// gap in the middle of the cabin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptySeat {
+    Front,
+    Interior,
+    Back,
+}
+
+// Largest cabin the report draws a seat chart for. Layouts allow seat IDs up to 32 bits, far
+// too many seats to print one character each.
+const MAX_CHART_SEATS: u64 = 1 << 16;
+
+// List runs of seat IDs for the report, writing a run of one seat as just its ID, e.g. "0-47, 52"
+fn show_ids(runs: &[(u32, u32)]) -> String {
+    if runs.is_empty() {
+        return String::from("none");
+    }
+
+    let runs: Vec<String> = runs
+        .iter()
+        .map(|&(start, end)| if start == end { start.to_string() } else { format!("{}-{}", start, end) })
+        .collect();
+    runs.join(", ")
+}
+
+// Struct to hold the boarding passes for the occupied seats on the plane, by seat ID. Only seats
+// with a pass are stored, so even a layout with billions of seats costs nothing for the empty
+// ones.
+pub struct Occupancy {
+    layout: SeatLayout,
+    seats: BTreeMap<u32, Vec<String>>,
+}
+
+impl Occupancy {
+    // Decode each of the `passes` and file it under its seat
+    pub fn from_passes(layout: SeatLayout, passes: &[BoardingPass]) -> Result<Occupancy, String> {
+        let mut seats: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+        for pass in passes {
+            seats.entry(pass.seat_number()?).or_default().push(pass.to_string());
+        }
+
+        Ok(Occupancy{layout, seats})
+    }
+
+    // Seat IDs of the first and last occupied seats
+    fn bounds(&self) -> Option<(u32, u32)> {
+        let first = self.seats.keys().next()?;
+        let last = self.seats.keys().next_back()?;
+        Some((*first, *last))
+    }
+
+    // Seat ID of the very last seat on the plane
+    fn last_seat(&self) -> u32 {
+        (self.layout.seat_count() - 1) as u32
+    }
+
+    // Classify the seat with the given `seat_id`, or None if it has a boarding pass
+    pub fn empty_seat(&self, seat_id: u32) -> Option<EmptySeat> {
+        if self.seats.contains_key(&seat_id) {
+            return None;
+        }
+        match self.bounds() {
+            Some((first, _)) if seat_id < first => Some(EmptySeat::Front),
+            Some((_, last)) if seat_id > last => Some(EmptySeat::Back),
+            Some(_) => Some(EmptySeat::Interior),
+            None => Some(EmptySeat::Front),
+        }
+    }
+
+    // Every empty seat of the given `kind`, as runs of consecutive seat IDs (first, last) in seat
+    // ID order. Worked out from the occupied seats alone, without visiting every empty one.
+    pub fn empty_seats(&self, kind: EmptySeat) -> Vec<(u32, u32)> {
+        let (first, last) = match self.bounds() {
+            Some(x) => x,
+            None if kind == EmptySeat::Front => return vec![(0, self.last_seat())],
+            None => return Vec::new(),
+        };
+
+        match kind {
+            EmptySeat::Front if first > 0 => vec![(0, first - 1)],
+            EmptySeat::Back if last < self.last_seat() => vec![(last + 1, self.last_seat())],
+            EmptySeat::Interior => {
+                let occupied: Vec<u32> = self.seats.keys().copied().collect();
+                occupied
+                    .windows(2)
+                    .filter(|x| x[1] > x[0] + 1)
+                    .map(|x| (x[0] + 1, x[1] - 1))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // Every seat with more than one boarding pass, along with those passes
+    pub fn duplicates(&self) -> Vec<(u32, &[String])> {
+        self.seats
+            .iter()
+            .filter(|(_, x)| x.len() > 1)
+            .map(|(i, x)| (*i, x.as_slice()))
+            .collect()
+    }
+
+    // Number of rows that are empty from end to end at the front and back of the plane
+    fn empty_rows(&self) -> (u64, u64) {
+        let per_row = 1u64 << self.layout.col_bits;
+        let rows = 1u64 << self.layout.row_bits;
+        match self.bounds() {
+            Some((first, last)) => (u64::from(first) / per_row, rows - 1 - u64::from(last) / per_row),
+            None => (rows, 0),
+        }
+    }
+
+    // Write the summary of empty and duplicate seats, then the cabin as a seat chart with one
+    // line per row. In the chart, `#` is an occupied seat, `!` a seat with more than one pass,
+    // `o` an empty seat in the middle of the cabin and `.` a seat missing from the front or back.
+    // Cabins with more than `MAX_CHART_SEATS` seats only get the summary.
+    pub fn write_report<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        let (front_rows, back_rows) = self.empty_rows();
+        let seat_count = self.layout.seat_count();
+
+        writeln!(out, "{} of {} seats occupied", self.seats.len(), seat_count)?;
+        writeln!(out, "Empty rows at the front: {}, at the back: {}", front_rows, back_rows)?;
+        writeln!(out, "Empty seats at the front: {}", show_ids(&self.empty_seats(EmptySeat::Front)))?;
+        writeln!(out, "Empty seats at the back: {}", show_ids(&self.empty_seats(EmptySeat::Back)))?;
+        writeln!(out, "Empty seats in between: {}", show_ids(&self.empty_seats(EmptySeat::Interior)))?;
+        for (seat_id, passes) in self.duplicates() {
+            writeln!(out, "Duplicate passes for seat {}: {}", seat_id, passes.join(", "))?;
+        }
+        writeln!(out)?;
+
+        if seat_count > MAX_CHART_SEATS {
+            writeln!(out, "Too many seats to chart, the limit is {}", MAX_CHART_SEATS)?;
+            return Ok(());
+        }
+
+        // Split each row in two with an aisle down the middle
+        let per_row = 1usize << self.layout.col_bits;
+        let aisle = per_row / 2;
+        let row_count = seat_count as usize / per_row;
+        let row_width = row_count.saturating_sub(1).to_string().len();
+
+        for row in 0..row_count {
+            let mut line = format!("{:>width$} ", row, width = row_width);
+            for col in 0..per_row {
+                if col == aisle && per_row > 1 {
+                    line.push(' ');
+                }
+                let seat_id = (row * per_row + col) as u32;
+                let passes = self.seats.get(&seat_id).map_or(0, |x| x.len());
+                line.push(match (passes, self.empty_seat(seat_id)) {
+                    (0, Some(EmptySeat::Interior)) => 'o',
+                    (0, _) => '.',
+                    (1, _) => '#',
+                    _ => '!',
+                });
+            }
+            writeln!(out, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+// Struct to describe the seating on an aircraft, as seen by its boarding passes: how many
+// characters pick the row and the column, and which letters mean 'lower half' and 'upper half'
+// for each. A seat ID is the row shifted left by the number of column bits, plus the column.
+#[derive(Debug, Clone, Copy)]
+pub struct SeatLayout {
+    pub row_bits: u32,
+    pub col_bits: u32,
+    pub row_letters: (char, char), // (lower half, upper half)
+    pub col_letters: (char, char), // (lower half, upper half)
+}
+
+// The aircraft from the puzzle: 128 rows picked with F/B, and 8 columns picked with L/R
+impl Default for SeatLayout {
+    fn default() -> SeatLayout {
+        SeatLayout {
+            row_bits: 7,
+            col_bits: 3,
+            row_letters: ('F', 'B'),
+            col_letters: ('L', 'R'),
+        }
+    }
+}
+
+impl SeatLayout {
+    // Parses a layout from a command line argument like "8,2", for 8 row characters and 2 column
+    // characters with the default letters, or "8,2,FBLR" to give the letters as well
+    pub fn from_spec(spec: &str) -> Option<SeatLayout> {
+        let parts: Vec<&str> = spec.split(',').collect();
+        let mut layout = SeatLayout::default();
+
+        match parts[..] {
+            [rows, cols] | [rows, cols, _] => {
+                layout.row_bits = rows.trim().parse().ok()?;
+                layout.col_bits = cols.trim().parse().ok()?;
+            }
+            _ => return None,
+        }
+        if let [_, _, letters] = parts[..] {
+            let letters: Vec<char> = letters.trim().chars().collect();
+            match letters[..] {
+                [f, b, l, r] => {
+                    layout.row_letters = (f, b);
+                    layout.col_letters = (l, r);
+                }
+                _ => return None,
+            }
+        }
+
+        // Seat IDs have to fit in a u32, and every letter has to mean just one thing
+        let mut letters = vec![layout.row_letters.0, layout.row_letters.1];
+        letters.extend([layout.col_letters.0, layout.col_letters.1]);
+        letters.sort_unstable();
+        letters.dedup();
+        if layout.row_bits + layout.col_bits > 32 || letters.len() != 4 {
+            return None;
+        }
+
+        Some(layout)
+    }
+
+    // Number of characters on a boarding pass for this layout
+    pub fn pass_len(&self) -> usize {
+        (self.row_bits + self.col_bits) as usize
+    }
+
+    // Number of seats on the aircraft, including any that don't exist at the very front or back
+    pub fn seat_count(&self) -> u64 {
+        1 << (self.row_bits + self.col_bits)
+    }
+}
@@ -0,0 +1,90 @@
+// This module holds the answers from the customs declaration forms. Each person's answers are
+// packed into the low 26 bits of a u32, one bit per question 'a' through 'z'.
+
+// The questions one person answered "yes" to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answers(u32);
+
+// Number of questions on the form
+pub const QUESTIONS: u32 = 26;
+
+impl Answers {
+    // Parses a line from the input file, one letter per question answered "yes". Returns None if
+    // the line contains anything other than the letters 'a' through 'z'.
+    pub fn from_line(line: &str) -> Option<Answers> {
+        let mut bits = 0;
+        for c in line.chars() {
+            if !c.is_ascii_lowercase() {
+                return None;
+            }
+            bits |= 1 << (c as u32 - 'a' as u32);
+        }
+
+        Some(Answers(bits))
+    }
+
+    // Number of questions answered "yes"
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    // Was the question with the given index (0 for 'a' through 25 for 'z') answered "yes"?
+    pub fn contains(&self, question: u32) -> bool {
+        self.0 & (1 << question) != 0
+    }
+}
+
+// The answers for a whole group of people
+#[derive(Debug)]
+pub struct Group {
+    people: Vec<Answers>,
+}
+
+impl Group {
+    pub fn new(people: Vec<Answers>) -> Group {
+        Group { people }
+    }
+
+    // Number of people in the group
+    pub fn size(&self) -> usize {
+        self.people.len()
+    }
+
+    // Number of people in the group who answered "yes" to the question with the given index
+    pub fn yes_count(&self, question: u32) -> usize {
+        self.people.iter().filter(|x| x.contains(question)).count()
+    }
+
+    // The questions answered "yes" by at least `k` people in the group. A question nobody answered
+    // never counts, so `k` of 0 is treated as 1.
+    pub fn answered_by_at_least(&self, k: usize) -> Answers {
+        let k = k.max(1);
+        let mut bits = 0;
+        for question in 0..QUESTIONS {
+            let yes = self.yes_count(question);
+            if yes >= k {
+                bits |= 1 << question;
+            }
+        }
+
+        Answers(bits)
+    }
+
+    // The questions answered "yes" by anyone in the group
+    pub fn answered_by_any(&self) -> Answers {
+        Answers(self.people.iter().fold(0, |bits, x| bits | x.0))
+    }
+
+    // The questions answered "yes" by everyone in the group
+    pub fn answered_by_all(&self) -> Answers {
+        if self.people.is_empty() {
+            return Answers(0);
+        }
+        Answers(self.people.iter().fold(u32::MAX, |bits, x| bits & x.0))
+    }
+
+    // The questions answered "yes" by more than half of the group
+    pub fn answered_by_majority(&self) -> Answers {
+        self.answered_by_at_least(self.size() / 2 + 1)
+    }
+}
@@ -1,10 +1,10 @@
-use std::collections::HashSet;
+use crate::answers::{Answers, Group};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Error};
+use std::io::{BufRead, BufReader, Error, ErrorKind};
 
-// Function to read in lines from an input file and convert them to a Vec<String>
-pub fn read_input(filename: &str) -> Result<Vec<Vec<HashSet<char>>>, Error> {
-    let file = File::open(&filename)?; // Open file or panic
+// Function to read in lines from an input file and convert them to a Vec<Group>
+pub fn read_input(filename: &str) -> Result<Vec<Group>, Error> {
+    let file = File::open(filename)?; // Open file or panic
     let br = BufReader::new(file); // Create read buffer
     let mut v = vec![]; // Initialize empty vector
 
@@ -13,17 +13,23 @@ pub fn read_input(filename: &str) -> Result<Vec<Vec<HashSet<char>>>, Error> {
     for line in br.lines() { 
         let line_string = line?.trim().to_string();
         if line_string.is_empty() {
-            v.push(group);
+            if !group.is_empty() {
+                v.push(Group::new(group));
+            }
             group = vec![]
         } else {
-            let mut person = HashSet::new();
-            for c in line_string.chars() {
-                person.insert(c);
+            match Answers::from_line(&line_string) {
+                Some(person) => group.push(person),
+                None => {
+                    let message = format!("'{}' is not a list of answers a-z", line_string);
+                    return Err(Error::new(ErrorKind::InvalidData, message));
+                }
             }
-            group.push(person);
         }
     }
-    v.push(group); // Get the last group
+    if !group.is_empty() {
+        v.push(Group::new(group)); // Get the last group
+    }
 
-    return Ok(v); // Return data
+    Ok(v) // Return data
 }
@@ -96,31 +96,72 @@
 // of those counts?
 
 
+mod answers;
 mod fileio;
 mod part_one;
 mod part_two;
+mod stats;
 
-use std::collections::HashSet;
+use answers::Group;
+use stats::Report;
+use std::env;
+use std::io;
 use std::time::Instant;
 
 // Timing function, given the function to run and the input arguments, runs
 // the function with the given input and prints the time to run to the
 // console
-fn time_it(f: fn(&Vec<Vec<HashSet<char>>>) -> (), group_answers: &Vec<Vec<HashSet<char>>>) {
+fn time_it(f: fn(&[Group]), group_answers: &[Group]) {
     let start = Instant::now();
-    f(&group_answers);
+    f(group_answers);
     let duration = start.elapsed();
 
     println!("Solved in: {:?}\n", duration);
 }
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
     // Read and parse the input file
     let input = match fileio::read_input("../input.txt") {
         Ok(x) => x,
         Err(e) => panic!("Error: {}", e),
     };
 
+    // Pass `--quorum <k>` to sum the counts of questions answered "yes" by at least `k` people in
+    // each group, or `--quorum majority` for questions answered "yes" by more than half of each
+    // group, instead of solving the puzzle
+    if let Some(pos) = args.iter().position(|arg| arg == "--quorum") {
+        let quorum = args.get(pos + 1).map(|x| x.as_str());
+        let sum_group_counts: u32 = match quorum {
+            Some("majority") => input.iter().map(|x| x.answered_by_majority().count()).sum(),
+            Some(k) => match k.parse() {
+                Ok(k) => input.iter().map(|x| x.answered_by_at_least(k).count()).sum(),
+                Err(_) => panic!("Expected a number or 'majority' after --quorum, not {}", k),
+            },
+            None => panic!("Expected a number or 'majority' after --quorum"),
+        };
+        println!("\nQuestions answered by the quorum: {}", sum_group_counts);
+        return;
+    }
+
+    // Pass `--stats` to print a per-question breakdown of the answers and the distribution of
+    // group sizes, instead of solving the puzzle. `--stats csv` prints the per-question breakdown
+    // as CSV, and `--stats sizes-csv` the group size distribution.
+    if let Some(pos) = args.iter().position(|arg| arg == "--stats") {
+        let report = Report::new(&input);
+        let mut out = io::stdout().lock();
+        let result = match args.get(pos + 1).map(|x| x.as_str()) {
+            Some("csv") => report.write_question_csv(&mut out),
+            Some("sizes-csv") => report.write_group_size_csv(&mut out),
+            _ => report.write_text(&mut out),
+        };
+        if let Err(e) = result {
+            panic!("Error: {}", e);
+        }
+        return;
+    }
+
     time_it(part_one::sum_answer_counts, &input); // 6291
     time_it(part_two::sum_answer_counts, &input); // 3052
 }
@@ -0,0 +1,120 @@
+// This module breaks the customs declaration answers down per question, rather than collapsing
+// them into a single total, and exports the breakdown as CSV.
+
+use crate::answers::{Group, QUESTIONS};
+use std::collections::BTreeMap;
+use std::io::{Error, Write};
+
+// Statistics for a single question
+#[derive(Debug, Default)]
+pub struct QuestionStats {
+    pub groups_yes: usize,     // Groups where anyone answered "yes"
+    pub people_yes: usize,     // People who answered "yes"
+    pub unanimous: usize,      // Groups where everyone answered "yes"
+}
+
+// Statistics for a whole batch of groups
+#[derive(Debug)]
+pub struct Report {
+    pub groups: usize,
+    pub people: usize,
+    pub questions: Vec<QuestionStats>,      // Indexed 0 for 'a' through 25 for 'z'
+    pub group_sizes: BTreeMap<usize, usize>, // Group size -> number of groups that size
+}
+
+// Letter for the question with the given index
+fn letter(question: usize) -> char {
+    (b'a' + question as u8) as char
+}
+
+impl Report {
+    // Tally up the answers for every question across all of the groups
+    pub fn new(group_answers: &[Group]) -> Report {
+        let mut questions: Vec<QuestionStats> = (0..QUESTIONS).map(|_| QuestionStats::default()).collect();
+        let mut group_sizes = BTreeMap::new();
+
+        for group in group_answers {
+            *group_sizes.entry(group.size()).or_insert(0) += 1;
+            for (question, stats) in questions.iter_mut().enumerate() {
+                let yes = group.yes_count(question as u32);
+                stats.people_yes += yes;
+                if yes > 0 {
+                    stats.groups_yes += 1;
+                }
+                if yes > 0 && yes == group.size() {
+                    stats.unanimous += 1;
+                }
+            }
+        }
+
+        Report {
+            groups: group_answers.len(),
+            people: group_answers.iter().map(|x| x.size()).sum(),
+            questions,
+            group_sizes,
+        }
+    }
+
+    // Of the groups where anyone answered the question at `index` "yes", the share where everyone
+    // did
+    pub fn unanimous_share(&self, index: usize) -> f64 {
+        let stats = &self.questions[index];
+        if stats.groups_yes == 0 {
+            return 0.0;
+        }
+        stats.unanimous as f64 / stats.groups_yes as f64
+    }
+
+    // Write the report as plain text, one line per question followed by the group sizes
+    pub fn write_text<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        writeln!(out, "{} groups, {} people", self.groups, self.people)?;
+        writeln!(out)?;
+        writeln!(out, "question  groups  people  unanimous")?;
+        for (i, stats) in self.questions.iter().enumerate() {
+            writeln!(
+                out,
+                "{:>8}  {:>6}  {:>6}  {:>5} ({:>5.1}%)",
+                letter(i),
+                stats.groups_yes,
+                stats.people_yes,
+                stats.unanimous,
+                self.unanimous_share(i) * 100.0
+            )?;
+        }
+        writeln!(out)?;
+        writeln!(out, "group size  groups")?;
+        for (size, count) in &self.group_sizes {
+            writeln!(out, "{:>10}  {:>6}", size, count)?;
+        }
+
+        Ok(())
+    }
+
+    // Write the per-question statistics as CSV, with a header row
+    pub fn write_question_csv<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        writeln!(out, "question,groups_yes,people_yes,unanimous_groups,unanimous_share")?;
+        for (i, stats) in self.questions.iter().enumerate() {
+            writeln!(
+                out,
+                "{},{},{},{},{:.4}",
+                letter(i),
+                stats.groups_yes,
+                stats.people_yes,
+                stats.unanimous,
+                self.unanimous_share(i)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Write the group size distribution as CSV, with a header row
+    pub fn write_group_size_csv<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        writeln!(out, "group_size,groups")?;
+        for (size, count) in &self.group_sizes {
+            writeln!(out, "{},{}", size, count)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use crate::parser::Rule;
+use std::collections::HashMap;
+
+// Index of a bag colour in a `BagGraph`
+pub type BagId = usize;
+
+// The bag rules as a directed graph. Every bag colour is interned to a `BagId` once, and each
+// bag keeps both the bags it holds (forward edges) and the bags that hold it (reverse edges),
+// so questions in either direction are a single walk over the graph.
+#[derive(Debug, Default)]
+pub struct BagGraph {
+    names: Vec<String>,
+    ids: HashMap<String, BagId>,
+    contents: Vec<Vec<(BagId, u32)>>,   // Bag -> (bag it holds, how many)
+    containers: Vec<Vec<(BagId, u32)>>, // Bag -> (bag that holds it, how many)
+    defined: Vec<bool>,                 // Bag -> does it have a rule of its own?
+}
+
+impl BagGraph {
+    // Build the graph from the parsed bag rules. If a bag has more than one rule, only the first
+    // one is used; `validate::validate()` reports the others.
+    pub fn from_rules(rules: &[Rule]) -> BagGraph {
+        let mut graph = BagGraph::default();
+
+        for rule in rules {
+            let outer = graph.intern(&rule.bag);
+            if graph.defined[outer] {
+                continue;
+            }
+            graph.defined[outer] = true;
+            for (inner, count) in &rule.contents {
+                let inner = graph.intern(inner);
+                graph.contents[outer].push((inner, *count));
+                graph.containers[inner].push((outer, *count));
+            }
+        }
+
+        graph
+    }
+
+    // Get the id for the bag called `name`, adding it to the graph if it's new
+    fn intern(&mut self, name: &str) -> BagId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.contents.push(Vec::new());
+        self.containers.push(Vec::new());
+        self.defined.push(false);
+        id
+    }
+
+    // Get the id for the bag called `name`, if there is one
+    pub fn id(&self, name: &str) -> Option<BagId> {
+        self.ids.get(name).copied()
+    }
+
+    // Get the name of the bag with the given `id`
+    pub fn name(&self, id: BagId) -> &str {
+        &self.names[id]
+    }
+
+    // Does the bag with the given `id` have a rule of its own, or is it only ever mentioned as
+    // the contents of other bags?
+    pub fn is_defined(&self, id: BagId) -> bool {
+        self.defined[id]
+    }
+
+    // The bags directly inside the bag `id`, and how many of each
+    pub fn contents(&self, id: BagId) -> &[(BagId, u32)] {
+        &self.contents[id]
+    }
+
+    // Number of bag colours in the graph
+    pub fn bag_count(&self) -> usize {
+        self.names.len()
+    }
+
+    // Walk the graph from `start` along the given edges, visiting each bag once, and return every
+    // bag reached (not including `start` itself, unless it's part of a loop)
+    fn reachable(&self, start: BagId, edges: &[Vec<(BagId, u32)>]) -> Vec<BagId> {
+        let mut seen = vec![false; self.bag_count()];
+        let mut stack = vec![start];
+        let mut found = Vec::new();
+
+        while let Some(bag) = stack.pop() {
+            for &(next, _) in &edges[bag] {
+                if !seen[next] {
+                    seen[next] = true;
+                    found.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+
+        found
+    }
+
+    // Every bag that can eventually hold the bag `id`
+    pub fn ancestors(&self, id: BagId) -> Vec<BagId> {
+        self.reachable(id, &self.containers)
+    }
+
+    // Every bag that can eventually end up inside the bag `id`
+    pub fn descendants(&self, id: BagId) -> Vec<BagId> {
+        self.reachable(id, &self.contents)
+    }
+
+    // Total number of bags inside the bag `id`, counting bags inside of bags. Each bag's total is
+    // worked out once and reused, since the same bag shows up inside many others. Fails if a bag
+    // can end up inside itself, since then there's no end to the count, or if the count doesn't
+    // fit in a u64.
+    pub fn total_contained(&self, id: BagId) -> Result<u64, TotalError> {
+        let mut totals: Vec<Total> = vec![Total::Unknown; self.bag_count()];
+
+        // Each stack entry is a bag whose total is being worked out, how many of its contents
+        // have been counted so far, and the count so far. Using our own stack keeps deep rule
+        // sets from overflowing the real one.
+        let mut stack: Vec<(BagId, usize, u64)> = vec![(id, 0, 0)];
+        totals[id] = Total::InProgress;
+
+        while let Some(top) = stack.last_mut() {
+            let (bag, next, total) = *top;
+            match self.contents[bag].get(next) {
+                Some(&(inner, count)) => match totals[inner] {
+                    // Count child bags plus their contents
+                    Total::Known(inner_total) => {
+                        top.1 += 1;
+                        top.2 = inner_total
+                            .checked_add(1)
+                            .and_then(|x| x.checked_mul(u64::from(count)))
+                            .and_then(|x| x.checked_add(total))
+                            .ok_or(TotalError::Overflow)?;
+                    }
+                    Total::InProgress => return Err(TotalError::Cycle),
+                    Total::Unknown => {
+                        totals[inner] = Total::InProgress;
+                        stack.push((inner, 0, 0));
+                    }
+                },
+                None => {
+                    totals[bag] = Total::Known(total);
+                    stack.pop();
+                }
+            }
+        }
+
+        match totals[id] {
+            Total::Known(total) => Ok(total),
+            _ => unreachable!("every bag on the stack is counted before it's popped"),
+        }
+    }
+}
+
+// Why `total_contained()` couldn't count the bags
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TotalError {
+    Cycle,    // A bag can end up inside itself
+    Overflow, // The count doesn't fit in a u64
+}
+
+// Progress of working out the total for a bag in `total_contained()`
+#[derive(Debug, Clone, Copy)]
+enum Total {
+    Unknown,
+    InProgress,
+    Known(u64),
+}
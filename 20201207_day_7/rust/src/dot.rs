@@ -0,0 +1,64 @@
+use crate::bag_graph::{BagGraph, BagId};
+use std::io::{Error, Write};
+
+// Which part of the graph to export
+#[derive(Debug, Clone, Copy)]
+pub enum Scope {
+    All,             // Every bag
+    Inside(BagId),   // The bag and every bag that can end up inside it
+    Holding(BagId),  // The bag and every bag that can eventually hold it
+}
+
+// Quote a bag name for use as a DOT identifier
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Write the bag graph in GraphViz DOT format, with an edge from each bag to each bag it holds,
+// labelled with how many. Only the bags in `scope` are written, and the `highlight` bag, if any,
+// is filled in so it stands out.
+pub fn write_dot<W: Write>(
+    graph: &BagGraph,
+    scope: Scope,
+    highlight: Option<BagId>,
+    out: &mut W,
+) -> Result<(), Error> {
+    let related = match scope {
+        Scope::All => None,
+        Scope::Inside(id) => Some((id, graph.descendants(id))),
+        Scope::Holding(id) => Some((id, graph.ancestors(id))),
+    };
+    let mut included = vec![related.is_none(); graph.bag_count()];
+    if let Some((id, related)) = related {
+        included[id] = true;
+        for bag in related {
+            included[bag] = true;
+        }
+    }
+
+    writeln!(out, "digraph bags {{")?;
+    writeln!(out, "    node [shape=box];")?;
+    for bag in (0..graph.bag_count()).filter(|&x| included[x]) {
+        if Some(bag) == highlight {
+            writeln!(out, "    {} [style=filled, fillcolor=gold];", quoted(graph.name(bag)))?;
+        } else {
+            writeln!(out, "    {};", quoted(graph.name(bag)))?;
+        }
+    }
+    for bag in (0..graph.bag_count()).filter(|&x| included[x]) {
+        for &(inner, count) in graph.contents(bag) {
+            if included[inner] {
+                writeln!(
+                    out,
+                    "    {} -> {} [label=\"{}\"];",
+                    quoted(graph.name(bag)),
+                    quoted(graph.name(inner)),
+                    count
+                )?;
+            }
+        }
+    }
+    writeln!(out, "}}")?;
+
+    Ok(())
+}
@@ -79,21 +79,26 @@
 // How many individual bags are required inside your single shiny gold bag?
 
 
-use std::collections::HashMap;
+use bag_graph::BagGraph;
+use dot::Scope;
+use std::env;
+use std::fs::File;
+use std::io;
 use std::time::Instant;
 
+mod bag_graph;
+mod dot;
 mod fileio;
+mod parser;
 mod part_one;
 mod part_two;
+mod queries;
+mod validate;
 
 // Timing function, given the function to run and the input arguments, runs
 // the function with the given input and prints the time to run to the
 // console
-fn time_it(
-    f: fn(&str, &HashMap<String, HashMap<String, u32>>) -> (), 
-    name: &str, 
-    bags: &HashMap<String, HashMap<String, u32>>
-) {
+fn time_it(f: fn(&str, &BagGraph), name: &str, bags: &BagGraph) {
     let start = Instant::now();
     f(name, bags);
     let duration = start.elapsed();
@@ -101,14 +106,142 @@ fn time_it(
     println!("Solved in: {:?}\n", duration);
 }
 
+// Bag names in the rules always end in " bag", so allow leaving it off on the command line, as in
+// `--inside "shiny gold"`
+fn bag_name(arg: &str) -> String {
+    let arg = arg.trim();
+    let arg = arg.strip_suffix('s').filter(|x| x.ends_with(" bag")).unwrap_or(arg);
+    if arg.ends_with(" bag") { arg.to_string() } else { format!("{} bag", arg) }
+}
+
+// Get the value following the flag `name` in the command line arguments
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let pos = args.iter().position(|arg| arg == name)?;
+    match args.get(pos + 1) {
+        Some(x) => Some(x),
+        None => panic!("Expected a value after {}", name),
+    }
+}
+
 fn main() {
-    // Read and parse the input file
-    let input = match fileio::read_input("../input.txt") {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Read and parse the input file, or the rules file given with `--rules <file>`
+    let filename = arg_value(&args, "--rules").unwrap_or("../input.txt");
+    let input = match fileio::read_input(filename) {
         Ok(x) => x,
         Err(e) => panic!("Error: {}", e),
     };
+    let bags = BagGraph::from_rules(&input);
+
+    // Pass `--validate` to list every problem with the rules, including bags that have nothing
+    // to do with the shiny gold bag, instead of solving the puzzle
+    if args.iter().any(|arg| arg == "--validate") {
+        let issues = validate::validate(&input, &bags, Some("shiny gold bag"));
+        for issue in &issues {
+            println!("{}", issue);
+        }
+        let errors = issues.iter().filter(|x| x.is_error()).count();
+        println!("\nFound {} error(s) and {} warning(s) in the rules.", errors, issues.len() - errors);
+        return;
+    }
+
+    // Pass `--dot` to write the rules as a GraphViz graph instead of solving the puzzle, to the
+    // console or to the file given with `--output <file>`. `--inside <bag>` keeps only that bag
+    // and the bags that can end up inside it, `--holding <bag>` only that bag and the bags that
+    // can hold it. Either way that bag is highlighted, or pick one with `--highlight <bag>`.
+    if args.iter().any(|arg| arg == "--dot") {
+        let find = |flag: &str| {
+            arg_value(&args, flag).map(|x| match bags.id(&bag_name(x)) {
+                Some(id) => id,
+                None => panic!("No rule mentions a {}", bag_name(x)),
+            })
+        };
+        let scope = match (find("--inside"), find("--holding")) {
+            (Some(id), None) => Scope::Inside(id),
+            (None, Some(id)) => Scope::Holding(id),
+            (None, None) => Scope::All,
+            (Some(_), Some(_)) => panic!("Use only one of --inside and --holding"),
+        };
+        let highlight = find("--highlight").or(match scope {
+            Scope::Inside(id) | Scope::Holding(id) => Some(id),
+            Scope::All => None,
+        });
+
+        let result = match arg_value(&args, "--output") {
+            Some(filename) => File::create(filename)
+                .and_then(|mut file| dot::write_dot(&bags, scope, highlight, &mut file)),
+            None => dot::write_dot(&bags, scope, highlight, &mut io::stdout().lock()),
+        };
+        if let Err(e) = result {
+            panic!("Error: {}", e);
+        }
+        return;
+    }
+
+    // Otherwise, stop before solving if there are any errors in the rules
+    let errors: Vec<validate::Issue> = validate::validate(&input, &bags, None)
+        .into_iter()
+        .filter(|x| x.is_error())
+        .collect();
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("{}", error);
+        }
+        println!("\nCan't solve with {} error(s) in the rules.", errors.len());
+        return;
+    }
+
+    // Look up a bag named on the command line
+    let find = |name: &str| match bags.id(&bag_name(name)) {
+        Some(id) => id,
+        None => panic!("No rule mentions a {}", bag_name(name)),
+    };
+    let show_chain = |chain: Option<Vec<usize>>| match chain {
+        Some(chain) => {
+            let names: Vec<&str> = chain.iter().map(|&x| bags.name(x)).collect();
+            format!("{} (length {})", names.join(" -> "), chain.len() - 1)
+        }
+        None => String::from("none"),
+    };
+
+    // Pass `--chain <outer bag> <inner bag>` to find the shortest and longest ways the inner bag
+    // can end up inside the outer one
+    if let Some(pos) = args.iter().position(|arg| arg == "--chain") {
+        let (outer, inner) = match (args.get(pos + 1), args.get(pos + 2)) {
+            (Some(outer), Some(inner)) => (find(outer), find(inner)),
+            _ => panic!("Expected two bags after --chain"),
+        };
+        println!("Shortest: {}", show_chain(queries::shortest_chain(&bags, outer, inner)));
+        println!("Longest: {}", show_chain(queries::longest_chain(&bags, outer, inner)));
+        return;
+    }
+
+    // Pass `--depth <bag>` to find how many levels of bags can be nested inside that bag, or
+    // `--depth all` for the bag with the deepest nesting of all
+    if let Some(bag) = arg_value(&args, "--depth") {
+        if bag == "all" {
+            match queries::deepest_bag(&bags) {
+                Some((id, depth)) => println!("The {} nests {} levels deep.", bags.name(id), depth),
+                None => println!("There are no bags."),
+            }
+        } else {
+            let id = find(bag);
+            println!("The {} nests {} levels deep.", bags.name(id), queries::nesting_depth(&bags, id));
+        }
+        return;
+    }
+
+    // Pass `--breakdown <bag>` to list everything inside that bag as a tree, with the number of
+    // bags at each level
+    if let Some(bag) = arg_value(&args, "--breakdown") {
+        if let Err(e) = queries::write_breakdown(&bags, find(bag), &mut io::stdout().lock()) {
+            panic!("Error: {}", e);
+        }
+        return;
+    }
 
     // Run the parts and report the results and timing
-    time_it(part_one::solve, "shiny gold bag", &input); // 296
-    time_it(part_two::solve, "shiny gold bag", &input); // 9339
+    time_it(part_one::solve, "shiny gold bag", &bags); // 296
+    time_it(part_two::solve, "shiny gold bag", &bags); // 9339
 }
@@ -0,0 +1,199 @@
+// Parser for the bag rule language. A line is split into tokens first, so that extra spaces,
+// counts of any size, "bag" or "bags", and a missing full stop at the end don't matter:
+//
+//     rule     = name bag "contain" contents [ "." ]
+//     contents = "no" "other" bag
+//              | count name bag { "," count name bag }
+//     bag      = "bag" | "bags"
+//     name     = word { word }
+//
+// Bag names are stored in the singular, e.g. "shiny gold bag".
+
+use std::fmt;
+
+// A single line from the rules file: the bag it describes, and the bags it holds. A bag listed
+// twice in `contents` is kept twice, along with the line number, so that `validate::validate()`
+// can report it.
+#[derive(Debug)]
+pub struct Rule {
+    pub line: usize,
+    pub bag: String,
+    pub contents: Vec<(String, u32)>,
+}
+
+// A rule that couldn't be parsed, with the line and column (both counting from 1) where the
+// problem was found
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Number(&'a str),
+    Comma,
+    Period,
+}
+
+// Split a line into tokens, each with the column it starts at
+fn tokenize(text: &str, line: usize) -> Result<Vec<(usize, Token<'_>)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        let column = text[..start].chars().count() + 1;
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ',' || c == '.' {
+            chars.next();
+            tokens.push((column, if c == ',' { Token::Comma } else { Token::Period }));
+        } else if c.is_ascii_digit() || c.is_alphabetic() {
+            // Take the whole run of digits or letters
+            let digits = c.is_ascii_digit();
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if (digits && c.is_ascii_digit()) || (!digits && (c.is_alphabetic() || c == '-')) {
+                    end = i + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let word = &text[start..end];
+            tokens.push((column, if digits { Token::Number(word) } else { Token::Word(word) }));
+        } else {
+            return Err(ParseError { line, column, message: format!("unexpected character '{}'", c) });
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Walks through the tokens for one line
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    pos: usize,
+    line: usize,
+    end_column: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    // Column of the next token, or just past the end of the line if there are none left
+    fn column(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end_column, |(c, _)| *c)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError { line: self.line, column: self.column(), message })
+    }
+
+    // Describe the next token for error messages
+    fn found(&self) -> String {
+        match self.peek() {
+            Some(Token::Word(x)) | Some(Token::Number(x)) => format!("'{}'", x),
+            Some(Token::Comma) => String::from("','"),
+            Some(Token::Period) => String::from("'.'"),
+            None => String::from("the end of the line"),
+        }
+    }
+
+    // Take the given word, or fail
+    fn expect_word(&mut self, word: &str) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(Token::Word(x)) if *x == word => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => self.error(format!("expected '{}', found {}", word, self.found())),
+        }
+    }
+
+    // name bag: one or more words, then "bag" or "bags"
+    fn bag_name(&mut self) -> Result<String, ParseError> {
+        let mut words = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::Word("bag")) | Some(Token::Word("bags")) if !words.is_empty() => {
+                    self.pos += 1;
+                    words.push("bag");
+                    return Ok(words.join(" "));
+                }
+                Some(Token::Word("contain")) | Some(Token::Word("bag")) | Some(Token::Word("bags")) => {
+                    return self.error(format!("expected a bag colour, found {}", self.found()));
+                }
+                Some(Token::Word(x)) => {
+                    words.push(*x);
+                    self.pos += 1;
+                }
+                _ if words.is_empty() => {
+                    return self.error(format!("expected a bag colour, found {}", self.found()));
+                }
+                _ => return self.error(format!("expected 'bag' or 'bags', found {}", self.found())),
+            }
+        }
+    }
+
+    // count name bag
+    fn item(&mut self) -> Result<(String, u32), ParseError> {
+        let count = match self.peek() {
+            Some(Token::Number(x)) => match x.parse() {
+                Ok(count) => count,
+                Err(_) => return self.error(format!("count {} is too large", x)),
+            },
+            _ => return self.error(format!("expected a number of bags, found {}", self.found())),
+        };
+        self.pos += 1;
+        Ok((self.bag_name()?, count))
+    }
+
+    fn rule(&mut self) -> Result<Rule, ParseError> {
+        let bag = self.bag_name()?;
+        self.expect_word("contain")?;
+
+        let mut contents = Vec::new();
+        if let Some(Token::Word("no")) = self.peek() {
+            self.pos += 1;
+            self.expect_word("other")?;
+            match self.peek() {
+                Some(Token::Word("bag")) | Some(Token::Word("bags")) => self.pos += 1,
+                _ => return self.error(format!("expected 'bags', found {}", self.found())),
+            }
+        } else {
+            contents.push(self.item()?);
+            while let Some(Token::Comma) = self.peek() {
+                self.pos += 1;
+                contents.push(self.item()?);
+            }
+        }
+
+        // An optional full stop, then nothing else
+        if let Some(Token::Period) = self.peek() {
+            self.pos += 1;
+        }
+        if self.peek().is_some() {
+            return self.error(format!("expected the end of the rule, found {}", self.found()));
+        }
+
+        Ok(Rule { line: self.line, bag, contents })
+    }
+}
+
+// Parse one line of the rules file. `line` is its line number, for error messages.
+pub fn parse_rule(text: &str, line: usize) -> Result<Rule, ParseError> {
+    let tokens = tokenize(text, line)?;
+    let end_column = text.chars().count() + 1;
+    Parser { tokens, pos: 0, line, end_column }.rule()
+}
@@ -0,0 +1,222 @@
+// Queries about how bags nest inside each other, beyond the two the puzzle asks for. These all
+// assume the rules have no loops, which `validate::validate()` checks for.
+
+use crate::bag_graph::{BagGraph, BagId, TotalError};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Error, Write};
+
+// Most levels of nesting `write_breakdown()` lists before cutting the tree short
+const MAX_TREE_DEPTH: usize = 50;
+
+// The shortest chain of bags from `outer` down to `inner`, both included, found with a
+// breadth-first search. None if `inner` can't end up inside `outer`.
+pub fn shortest_chain(graph: &BagGraph, outer: BagId, inner: BagId) -> Option<Vec<BagId>> {
+    let mut previous: Vec<Option<BagId>> = vec![None; graph.bag_count()];
+    let mut queue = VecDeque::from(vec![outer]);
+
+    while let Some(bag) = queue.pop_front() {
+        if bag == inner && bag != outer {
+            break;
+        }
+        for &(next, _) in graph.contents(bag) {
+            if previous[next].is_none() && next != outer {
+                previous[next] = Some(bag);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    // Follow the trail back up from `inner`
+    let mut chain = vec![inner];
+    let mut bag = inner;
+    while bag != outer || chain.len() == 1 {
+        bag = previous[bag]?;
+        chain.push(bag);
+    }
+    chain.reverse();
+    Some(chain)
+}
+
+// Every bag that can end up inside any of the `starts`, along with the `starts` themselves, each
+// listed after all of the bags inside it, so going through the list in order sees a bag's
+// contents before the bag. Using our own stack keeps deep rule sets from overflowing the real one.
+fn post_order<I: IntoIterator<Item = BagId>>(graph: &BagGraph, starts: I) -> Vec<BagId> {
+    let mut seen = vec![false; graph.bag_count()];
+    let mut order = Vec::new();
+
+    for start in starts {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+
+        // Each stack entry is a bag on the current path, and how many of its contents have been
+        // searched so far
+        let mut path: Vec<(BagId, usize)> = vec![(start, 0)];
+        while let Some(top) = path.last_mut() {
+            let (bag, next) = *top;
+            top.1 += 1;
+            match graph.contents(bag).get(next) {
+                Some(&(inner, _)) => {
+                    if !seen[inner] {
+                        seen[inner] = true;
+                        path.push((inner, 0));
+                    }
+                }
+                None => {
+                    order.push(bag);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    order
+}
+
+// The longest chain of bags from `outer` down to `inner`, both included. None if `inner` can't
+// end up inside `outer`.
+pub fn longest_chain(graph: &BagGraph, outer: BagId, inner: BagId) -> Option<Vec<BagId>> {
+    // For each bag, the length of the longest chain from it down to `inner`, and the next bag in
+    // that chain, worked out for the bags inside it first
+    let mut longest: Vec<Option<(usize, BagId)>> = vec![None; graph.bag_count()];
+    for bag in post_order(graph, vec![outer]) {
+        let mut best: Option<(usize, BagId)> = None;
+        for &(next, _) in graph.contents(bag) {
+            let length = if next == inner { Some(1) } else { longest[next].map(|x| x.0 + 1) };
+            if let Some(length) = length {
+                if best.is_none_or(|(x, _)| length > x) {
+                    best = Some((length, next));
+                }
+            }
+        }
+        longest[bag] = best;
+    }
+
+    let mut chain = vec![outer];
+    let mut bag = outer;
+    loop {
+        let (_, next) = longest[bag]?;
+        chain.push(next);
+        if next == inner {
+            return Some(chain);
+        }
+        bag = next;
+    }
+}
+
+// How many levels of bags can be nested inside each bag in `order`, which must list the bags
+// inside a bag before the bag itself, as `post_order()` does. Bags not in `order` are left at 0.
+fn depths(graph: &BagGraph, order: &[BagId]) -> Vec<usize> {
+    let mut depths = vec![0; graph.bag_count()];
+    for &bag in order {
+        depths[bag] = graph
+            .contents(bag)
+            .iter()
+            .map(|&(next, _)| depths[next] + 1)
+            .max()
+            .unwrap_or(0);
+    }
+    depths
+}
+
+// How many levels of bags can be nested inside the bag `id`: 0 for a bag that holds nothing,
+// 1 for a bag that only holds empty bags, and so on
+pub fn nesting_depth(graph: &BagGraph, id: BagId) -> usize {
+    depths(graph, &post_order(graph, vec![id]))[id]
+}
+
+// The bag with the deepest nesting of all, and how deep it goes
+pub fn deepest_bag(graph: &BagGraph) -> Option<(BagId, usize)> {
+    let depths = depths(graph, &post_order(graph, 0..graph.bag_count()));
+    let mut deepest: Option<(BagId, usize)> = None;
+    for (id, &x) in depths.iter().enumerate() {
+        if deepest.is_none_or(|(_, best)| x > best) {
+            deepest = Some((id, x));
+        }
+    }
+    deepest
+}
+
+// Number of bags at each level of nesting inside the bag `id`, where the first entry is the bags
+// held directly. These add up to the puzzle's part two answer. Fails if any level has more bags
+// than fit in a u64.
+pub fn level_totals(graph: &BagGraph, id: BagId) -> Result<Vec<u64>, TotalError> {
+    let mut totals = Vec::new();
+    let mut level: HashMap<BagId, u64> = HashMap::new();
+    level.insert(id, 1);
+
+    loop {
+        let mut next_level: HashMap<BagId, u64> = HashMap::new();
+        for (&bag, &copies) in &level {
+            for &(inner, count) in graph.contents(bag) {
+                let copies_inside = next_level.entry(inner).or_insert(0);
+                *copies_inside = copies
+                    .checked_mul(u64::from(count))
+                    .and_then(|x| x.checked_add(*copies_inside))
+                    .ok_or(TotalError::Overflow)?;
+            }
+        }
+        if next_level.is_empty() {
+            return Ok(totals);
+        }
+        let total = next_level.values().try_fold(0u64, |sum, &x| sum.checked_add(x));
+        totals.push(total.ok_or(TotalError::Overflow)?);
+        level = next_level;
+    }
+}
+
+// Write everything inside the bag `id` as an indented tree, one line per rule, followed by the
+// number of bags at each level of nesting, or a note if there are too many to count. A bag that
+// shows up more than once only has its contents listed the first time, and the tree stops at
+// `MAX_TREE_DEPTH` levels, so the output doesn't blow up on rules that share or nest a lot of bags.
+pub fn write_breakdown<W: Write>(graph: &BagGraph, id: BagId, out: &mut W) -> Result<(), Error> {
+    let mut listed = vec![false; graph.bag_count()];
+    listed[id] = true;
+    writeln!(out, "{}", graph.name(id))?;
+
+    // Each stack entry is a bag whose contents are being listed, and how many have been so far
+    let mut stack: Vec<(BagId, usize)> = vec![(id, 0)];
+    while let Some(top) = stack.last_mut() {
+        let (bag, next) = *top;
+        top.1 += 1;
+        let (inner, count) = match graph.contents(bag).get(next) {
+            Some(&x) => x,
+            None => {
+                stack.pop();
+                continue;
+            }
+        };
+
+        let indent = "  ".repeat(stack.len());
+        if graph.contents(inner).is_empty() {
+            writeln!(out, "{}{} {}", indent, count, graph.name(inner))?;
+        } else if listed[inner] {
+            writeln!(out, "{}{} {} (see above)", indent, count, graph.name(inner))?;
+        } else if stack.len() == MAX_TREE_DEPTH {
+            writeln!(out, "{}{} {} ...", indent, count, graph.name(inner))?;
+        } else {
+            writeln!(out, "{}{} {}", indent, count, graph.name(inner))?;
+            listed[inner] = true;
+            stack.push((inner, 0));
+        }
+    }
+    writeln!(out)?;
+
+    let totals = match level_totals(graph, id) {
+        Ok(x) => x,
+        Err(_) => {
+            writeln!(out, "Too many bags to count")?;
+            return Ok(());
+        }
+    };
+    for (level, total) in totals.iter().enumerate() {
+        writeln!(out, "Level {}: {} bags", level + 1, total)?;
+    }
+    match totals.iter().try_fold(0u64, |sum, &x| sum.checked_add(x)) {
+        Some(total) => writeln!(out, "Total: {} bags", total)?,
+        None => writeln!(out, "Total: too many bags to count")?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use crate::bag_graph::{BagGraph, BagId};
+use crate::parser::Rule;
+use std::collections::HashMap;
+use std::fmt;
+
+// A problem found in a set of bag rules
+#[derive(Debug)]
+pub enum Issue {
+    // A bag can end up inside itself, following this path of bags back to the first one
+    Cycle(Vec<String>),
+
+    // A bag is held by other bags but never gets a rule of its own
+    Undefined { bag: String, used_on: Vec<usize> },
+
+    // A bag has more than one rule, on these lines
+    Duplicate { bag: String, lines: Vec<usize> },
+
+    // A bag is listed more than once in the contents of a single rule
+    Repeated { bag: String, inner: String, line: usize },
+
+    // A bag neither holds nor can be held by the bag being asked about, so its rule never matters
+    Unreachable(String),
+}
+
+impl Issue {
+    // Errors make the answers wrong or impossible to find; anything else is just a warning
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Issue::Unreachable(_))
+    }
+}
+
+// Join a list of line numbers for printing
+fn show_lines(lines: &[usize]) -> String {
+    let lines: Vec<String> = lines.iter().map(|x| x.to_string()).collect();
+    lines.join(", ")
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::Cycle(path) => write!(f, "error: bags contain each other: {}", path.join(" -> ")),
+            Issue::Undefined { bag, used_on } => {
+                write!(f, "error: {} has no rule, used on line(s) {}", bag, show_lines(used_on))
+            }
+            Issue::Duplicate { bag, lines } => {
+                write!(f, "error: {} has more than one rule, on lines {}", bag, show_lines(lines))
+            }
+            Issue::Repeated { bag, inner, line } => {
+                write!(f, "error: {} lists {} more than once, on line {}", bag, inner, line)
+            }
+            Issue::Unreachable(bag) => write!(f, "warning: {} is unreachable", bag),
+        }
+    }
+}
+
+// Find every loop in the graph with a depth-first search, recording the path back to the start of
+// the loop each time the search runs into a bag that is still on the current path
+fn find_cycles(graph: &BagGraph) -> Vec<Vec<String>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State { New, OnPath, Done }
+
+    let mut state = vec![State::New; graph.bag_count()];
+    let mut cycles = Vec::new();
+
+    for start in 0..graph.bag_count() {
+        if state[start] != State::New {
+            continue;
+        }
+
+        // Each stack entry is a bag on the current path, and how many of its contents have been
+        // searched so far. Using our own stack keeps deep rule sets from overflowing the real one.
+        let mut path: Vec<(BagId, usize)> = vec![(start, 0)];
+        state[start] = State::OnPath;
+
+        while let Some(top) = path.last_mut() {
+            let (bag, next) = *top;
+            top.1 += 1;
+            match graph.contents(bag).get(next) {
+                Some(&(inner, _)) => {
+                    match state[inner] {
+                        State::New => {
+                            state[inner] = State::OnPath;
+                            path.push((inner, 0));
+                        }
+                        State::OnPath => {
+                            let from = path.iter().position(|&(x, _)| x == inner).unwrap_or(0);
+                            let mut cycle: Vec<String> =
+                                path[from..].iter().map(|&(x, _)| graph.name(x).to_string()).collect();
+                            cycle.push(graph.name(inner).to_string());
+                            cycles.push(cycle);
+                        }
+                        State::Done => (),
+                    }
+                }
+                None => {
+                    state[bag] = State::Done;
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    cycles
+}
+
+// Check the rules for anything that would make the answers wrong: loops, bags without rules,
+// bags with more than one rule and bags listed twice in one rule. If `root` is given, also warn about every bag that has nothing to
+// do with it.
+pub fn validate(rules: &[Rule], graph: &BagGraph, root: Option<&str>) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = find_cycles(graph).into_iter().map(Issue::Cycle).collect();
+
+    // Bags that are used, but never defined
+    let mut used_on: HashMap<&str, Vec<usize>> = HashMap::new();
+    for rule in rules {
+        for (inner, _) in &rule.contents {
+            let is_defined = graph.id(inner).is_some_and(|x| graph.is_defined(x));
+            if !is_defined {
+                used_on.entry(inner).or_default().push(rule.line);
+            }
+        }
+    }
+    let mut undefined: Vec<(&str, Vec<usize>)> = used_on.into_iter().collect();
+    undefined.sort_by_key(|(_, lines)| lines[0]);
+    for (bag, lines) in undefined {
+        issues.push(Issue::Undefined { bag: bag.to_string(), used_on: lines });
+    }
+
+    // Bags defined more than once
+    let mut defined_on: HashMap<&str, Vec<usize>> = HashMap::new();
+    for rule in rules {
+        defined_on.entry(&rule.bag).or_default().push(rule.line);
+    }
+    let mut duplicates: Vec<(&str, Vec<usize>)> =
+        defined_on.into_iter().filter(|(_, lines)| lines.len() > 1).collect();
+    duplicates.sort_by_key(|(_, lines)| lines[0]);
+    for (bag, lines) in duplicates {
+        issues.push(Issue::Duplicate { bag: bag.to_string(), lines });
+    }
+
+    // Bags listed more than once in the same rule
+    for rule in rules {
+        let mut seen: Vec<&str> = Vec::new();
+        let mut repeated: Vec<&str> = Vec::new();
+        for (inner, _) in &rule.contents {
+            if !seen.contains(&inner.as_str()) {
+                seen.push(inner);
+            } else if !repeated.contains(&inner.as_str()) {
+                repeated.push(inner);
+            }
+        }
+        for inner in repeated {
+            issues.push(Issue::Repeated { bag: rule.bag.clone(), inner: inner.to_string(), line: rule.line });
+        }
+    }
+
+    // Bags that are neither above nor below the root
+    if let Some(root) = root.and_then(|x| graph.id(x)) {
+        let mut related = vec![false; graph.bag_count()];
+        related[root] = true;
+        for bag in graph.ancestors(root).into_iter().chain(graph.descendants(root)) {
+            related[bag] = true;
+        }
+        for (bag, is_related) in related.into_iter().enumerate() {
+            if !is_related {
+                issues.push(Issue::Unreachable(graph.name(bag).to_string()));
+            }
+        }
+    }
+
+    issues
+}
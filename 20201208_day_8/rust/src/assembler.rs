@@ -0,0 +1,174 @@
+// Assembler and disassembler for boot code with labels. Source is one instruction per line, as in
+// the puzzle input, with a few additions to make programs easier to write by hand:
+//
+//     # Comments run from '#' to the end of the line, and blank lines are ignored
+//     loop_start:         # A label names the address of the instruction after it
+//         acc +1
+//         jmp loop_start  # Jump offsets can be given as a label instead of a number
+//     done: nop +0        # A label can also go in front of an instruction on the same line
+//
+// The operations allowed, and the operands each one takes, come from the instruction set (see
+// `isa`). Labels start with a letter or '_', followed by letters, digits or '_'. A label after
+// the last instruction names the address just past the end of the program, where it terminates.
+// Plain puzzle input is valid source, so it can all be read through the assembler.
+
+use crate::instruction::{parse_operand, show_operand, Instruction};
+use crate::isa::{Isa, Opcode, OperandKind};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{Error, Write};
+
+// A line of source that couldn't be assembled, with the line number (counting from 1)
+#[derive(Debug)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// An operand of an instruction, before labels are resolved
+enum Operand<'a> {
+    Resolved(i32),
+    Label(&'a str),
+}
+
+// An instruction from the first pass over the source: its line, opcode and operands
+struct Statement<'a> {
+    line: usize,
+    opcode: &'static Opcode,
+    operands: Vec<Operand<'a>>,
+}
+
+fn is_label(word: &str) -> bool {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+// Turn source into a program, resolving each label to an offset relative to the instruction
+// that uses it
+pub fn assemble(source: &str, isa: &Isa) -> Result<Vec<Instruction>, AsmError> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut statements = Vec::new();
+
+    // First pass: note the address of every label, and split up the instructions
+    for (line_no, line) in source.lines().enumerate() {
+        let line_no = line_no + 1;
+        let error = |message: String| AsmError { line: line_no, message };
+        let mut text = line.split('#').next().unwrap_or("").trim();
+
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(error(format!("'{}' is not a valid label", label)));
+            }
+            if labels.insert(label, statements.len()).is_some() {
+                return Err(error(format!("Label '{}' is defined more than once", label)));
+            }
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let opcode = match isa.get(words[0]) {
+            Some(x) => x,
+            None => {
+                let expected = isa.mnemonics().join("/");
+                return Err(error(format!("Unknown operation '{}', expected one of {}", words[0], expected)));
+            }
+        };
+        if words.len() - 1 != opcode.operands.len() {
+            let count = opcode.operands.len();
+            return Err(error(format!("{} takes {} operand(s), got '{}'", words[0], count, text)));
+        }
+
+        let mut operands = Vec::with_capacity(opcode.operands.len());
+        for (&kind, word) in opcode.operands.iter().zip(&words[1..]) {
+            if kind == OperandKind::Offset && is_label(word) {
+                operands.push(Operand::Label(word));
+            } else {
+                operands.push(Operand::Resolved(parse_operand(kind, word).map_err(error)?));
+            }
+        }
+        statements.push(Statement { line: line_no, opcode, operands });
+    }
+
+    // Second pass: resolve the labels and build the instructions
+    let mut program = Vec::with_capacity(statements.len());
+    for (address, statement) in statements.iter().enumerate() {
+        let error = |message: String| AsmError { line: statement.line, message };
+        let mut args = Vec::with_capacity(statement.operands.len());
+        for operand in &statement.operands {
+            args.push(match operand {
+                Operand::Resolved(x) => *x,
+                Operand::Label(label) => {
+                    let target = labels.get(label).ok_or(error(format!("Undefined label '{}'", label)))?;
+                    *target as i32 - address as i32
+                }
+            });
+        }
+        program.push(Instruction::new(statement.opcode, &args));
+    }
+
+    Ok(program)
+}
+
+// Write the program as plain puzzle input, one instruction per line
+pub fn write_program<W: Write>(program: &[Instruction], out: &mut W) -> Result<(), Error> {
+    for instruction in program {
+        writeln!(out, "{}", instruction)?;
+    }
+    Ok(())
+}
+
+// Write the program as source with labels. Every address a jump offset points at gets a label,
+// `L<address>`, or `end` for the address just past the last instruction, and the instruction
+// refers to it by name. Offsets that point outside the program are left as numbers.
+// Assembling the result gives back the same program.
+pub fn write_source<W: Write>(program: &[Instruction], out: &mut W) -> Result<(), Error> {
+    let target = |address: usize, instruction: &Instruction| {
+        let target = address as i64 + i64::from(instruction.offset()?);
+        if (0..=program.len() as i64).contains(&target) {
+            Some(target as usize)
+        } else {
+            None
+        }
+    };
+
+    let mut labels = BTreeMap::new();
+    for (address, instruction) in program.iter().enumerate() {
+        if let Some(x) = target(address, instruction) {
+            let name = if x == program.len() { String::from("end") } else { format!("L{}", x) };
+            labels.insert(x, name);
+        }
+    }
+
+    for (address, instruction) in program.iter().enumerate() {
+        if let Some(label) = labels.get(&address) {
+            writeln!(out, "{}:", label)?;
+        }
+        let mut text = instruction.opcode.mnemonic.to_string();
+        for (&kind, &arg) in instruction.opcode.operands.iter().zip(instruction.operands()) {
+            text.push(' ');
+            match (kind, target(address, instruction)) {
+                (OperandKind::Offset, Some(x)) => text.push_str(&labels[&x]),
+                _ => text.push_str(&show_operand(kind, arg)),
+            }
+        }
+        writeln!(out, "    {}", text)?;
+    }
+    if let Some(label) = labels.get(&program.len()) {
+        writeln!(out, "{}:", label)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,352 @@
+use crate::instruction::{Instruction, REGISTER_NAMES};
+use crate::isa::Isa;
+use crate::machine::Machine;
+use std::fmt;
+use std::io::{BufRead, Error, Write};
+
+const HELP: &str = "\
+Commands:
+  step [N]              Run the next N instructions (default 1)
+  continue              Run until a breakpoint is hit or the program halts
+  break ADDR            Pause before running the instruction at ADDR
+  break acc OP VALUE    Pause when the accumulator comes to meet the condition (OP is
+                        one of == != < <= > >=)
+  break                 List the breakpoints
+  delete N              Remove breakpoint N
+  watch                 Turn reporting of every change to the accumulator on or off
+  patch ADDR INSTR      Replace the instruction at ADDR, e.g. 'patch 7 nop -4'
+  flip ADDR             Swap the instruction at ADDR for its counterpart, e.g. jmp for nop
+  list [ADDR] [N]       Show N instructions (default 9) around ADDR (default the pointer)
+  history [N]           Show the last N instructions run (default 10, at most 1000)
+  state                 Show the pointer, registers and number of steps run
+  reset                 Go back to the start, keeping breakpoints and patches
+  help                  Show this list
+  quit                  Leave the debugger";
+
+// A comparison against the accumulator for a breakpoint
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn from_symbol(symbol: &str) -> Option<Comparison> {
+        match symbol {
+            "==" => Some(Comparison::Eq),
+            "!=" => Some(Comparison::Ne),
+            "<" => Some(Comparison::Lt),
+            "<=" => Some(Comparison::Le),
+            ">" => Some(Comparison::Gt),
+            ">=" => Some(Comparison::Ge),
+            _ => None,
+        }
+    }
+
+    fn symbol(&self) -> &str {
+        match self {
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+
+    fn holds(&self, acc: i32, value: i32) -> bool {
+        match self {
+            Comparison::Eq => acc == value,
+            Comparison::Ne => acc != value,
+            Comparison::Lt => acc < value,
+            Comparison::Le => acc <= value,
+            Comparison::Gt => acc > value,
+            Comparison::Ge => acc >= value,
+        }
+    }
+}
+
+// Somewhere for `continue` to pause
+#[derive(Debug, Clone, Copy)]
+enum Breakpoint {
+    Address(usize),        // Before running the instruction at this address
+    Acc(Comparison, i32),  // As soon as the accumulator comes to meet the condition
+}
+
+impl Breakpoint {
+    // Parse the arguments to the `break` command
+    fn parse(args: &[&str]) -> Result<Breakpoint, String> {
+        match args {
+            [address] => match address.parse() {
+                Ok(x) => Ok(Breakpoint::Address(x)),
+                Err(_) => Err(format!("'{}' is not an address", address)),
+            },
+            ["acc", symbol, value] => {
+                let comparison = Comparison::from_symbol(symbol)
+                    .ok_or(format!("Unknown comparison '{}'", symbol))?;
+                let value = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+                Ok(Breakpoint::Acc(comparison, value))
+            }
+            _ => Err(String::from("Expected 'break ADDR' or 'break acc OP VALUE'")),
+        }
+    }
+
+    // Whether `continue` should pause here, given the accumulator before the last step. An acc
+    // breakpoint only fires when its condition starts to hold, so it doesn't fire again on every
+    // step while the accumulator stays put.
+    fn is_hit(&self, machine: &Machine, acc_before: i32) -> bool {
+        match self {
+            Breakpoint::Address(x) => machine.pointer() == *x as i64,
+            Breakpoint::Acc(comparison, value) => {
+                !comparison.holds(acc_before, *value) && comparison.holds(machine.acc(), *value)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Address(x) => write!(f, "address {}", x),
+            Breakpoint::Acc(comparison, value) => write!(f, "acc {} {}", comparison.symbol(), value),
+        }
+    }
+}
+
+// A stepping debugger around a Machine, driven by commands read one per line. Reads from any
+// BufRead and writes to any Write, so a session can be scripted by piping commands in.
+pub struct Debugger {
+    machine: Machine,
+    isa: Isa, // For parsing patched instructions
+    breakpoints: Vec<Option<Breakpoint>>, // Deleted breakpoints leave a gap, so numbers stay put
+    watch: bool,
+}
+
+impl Debugger {
+    pub fn new(machine: Machine, isa: Isa) -> Debugger {
+        Debugger { machine, isa, breakpoints: Vec::new(), watch: false }
+    }
+
+    // Read and run commands until `quit` or the end of the input
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, out: &mut W) -> Result<(), Error> {
+        let len = self.machine.program().len();
+        writeln!(out, "Loaded {} instructions. Type 'help' for a list of commands.", len)?;
+        self.show_next(out)?;
+
+        write!(out, "(dbg) ")?;
+        out.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if let Some((&command, args)) = words.split_first() {
+                match self.command(command, args, out) {
+                    Ok(true) => (),
+                    Ok(false) => return Ok(()),
+                    Err(message) => writeln!(out, "{}", message)?,
+                }
+            }
+            write!(out, "(dbg) ")?;
+            out.flush()?;
+        }
+
+        writeln!(out)
+    }
+
+    // Run a single command. Returns Ok(false) if it's time to quit, and Err with a message for
+    // the user if the command couldn't be run. Failures writing the output are treated the same
+    // way, since there's nowhere better to report them.
+    fn command<W: Write>(&mut self, command: &str, args: &[&str], out: &mut W) -> Result<bool, String> {
+        let io = |e: Error| format!("Error: {}", e);
+
+        match (command, args) {
+            ("step", _) | ("s", _) => {
+                let count = match args.first() {
+                    Some(x) => parse_number(x)?,
+                    None => 1,
+                };
+                for _ in 0..count {
+                    if !self.step(out).map_err(io)? {
+                        break;
+                    }
+                }
+                self.show_next(out).map_err(io)?;
+            }
+            ("continue", []) | ("c", []) => {
+                // Always run at least one instruction, so a breakpoint on the current address
+                // doesn't stop us where we are
+                loop {
+                    let acc_before = self.machine.acc();
+                    if !self.step(out).map_err(io)? {
+                        break;
+                    }
+                    let hit = self.breakpoints.iter().enumerate().find_map(|(i, x)| match x {
+                        Some(b) if b.is_hit(&self.machine, acc_before) => Some((i, *b)),
+                        _ => None,
+                    });
+                    if let Some((i, breakpoint)) = hit {
+                        writeln!(out, "Breakpoint {}: {}", i, breakpoint).map_err(io)?;
+                        break;
+                    }
+                }
+                self.show_next(out).map_err(io)?;
+            }
+            ("break", []) | ("b", []) => {
+                if self.breakpoints.iter().all(|x| x.is_none()) {
+                    writeln!(out, "No breakpoints").map_err(io)?;
+                }
+                for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                    if let Some(b) = breakpoint {
+                        writeln!(out, "Breakpoint {}: {}", i, b).map_err(io)?;
+                    }
+                }
+            }
+            ("break", _) | ("b", _) => {
+                let breakpoint = Breakpoint::parse(args)?;
+                self.breakpoints.push(Some(breakpoint));
+                writeln!(out, "Breakpoint {}: {}", self.breakpoints.len() - 1, breakpoint).map_err(io)?;
+            }
+            ("delete", [n]) => {
+                let n = parse_number(n)?;
+                match self.breakpoints.get_mut(n) {
+                    Some(x @ Some(_)) => *x = None,
+                    _ => return Err(format!("No breakpoint {}", n)),
+                }
+            }
+            ("watch", []) => {
+                self.watch = !self.watch;
+                let message = if self.watch { "Watching acc" } else { "Stopped watching acc" };
+                writeln!(out, "{}", message).map_err(io)?;
+            }
+            ("patch", [address, instruction @ ..]) => {
+                let address = self.address(address)?;
+                let instruction = Instruction::parse(&instruction.join(" "), &self.isa)?;
+                self.patch(address, instruction, out).map_err(io)?;
+            }
+            ("flip", [address]) => {
+                let address = self.address(address)?;
+                let instruction = self.machine.program()[address];
+                let flipped = instruction.flipped().ok_or(format!("Can't flip '{}'", instruction))?;
+                self.patch(address, flipped, out).map_err(io)?;
+            }
+            ("list", _) | ("l", _) => {
+                let center = match args.first() {
+                    Some(x) => self.address(x)?,
+                    None => self.machine.pointer().max(0) as usize,
+                };
+                let count = match args.get(1) {
+                    Some(x) => parse_number(x)?,
+                    None => 9,
+                };
+                self.list(center, count, out).map_err(io)?;
+            }
+            ("history", _) | ("h", _) => {
+                let count = match args.first() {
+                    Some(x) => parse_number(x)?,
+                    None => 10,
+                };
+                // Only the last `HISTORY_LIMIT` steps are kept, so number them back from the latest
+                let history = self.machine.history();
+                let first_step = self.machine.steps() - history.len() + 1;
+                let start = history.len().saturating_sub(count);
+                for (i, &address) in history.iter().enumerate().skip(start) {
+                    let instruction = self.machine.program()[address];
+                    writeln!(out, "step {:>5}: {:>5}  {}", first_step + i, address, instruction).map_err(io)?;
+                }
+            }
+            ("state", []) => {
+                // Other registers are only shown once something has been put in them
+                let mut registers = vec![format!("acc = {}", self.machine.acc())];
+                for (name, value) in REGISTER_NAMES.iter().zip(self.machine.registers()).skip(1) {
+                    if *value != 0 {
+                        registers.push(format!("{} = {}", name, value));
+                    }
+                }
+                let (pointer, steps) = (self.machine.pointer(), self.machine.steps());
+                writeln!(out, "pointer = {}, {}, {} steps run", pointer, registers.join(", "), steps).map_err(io)?;
+            }
+            ("reset", []) => {
+                self.machine.reset();
+                self.show_next(out).map_err(io)?;
+            }
+            ("help", []) => writeln!(out, "{}", HELP).map_err(io)?,
+            ("quit", []) | ("q", []) => return Ok(false),
+            _ => return Err(format!("Unknown command '{}', try 'help'", args_line(command, args))),
+        }
+
+        Ok(true)
+    }
+
+    // Run one instruction, reporting any change to the accumulator if it's being watched.
+    // Returns false if the program has halted; `show_next()` says why.
+    fn step<W: Write>(&mut self, out: &mut W) -> Result<bool, Error> {
+        let (address, before) = (self.machine.pointer(), self.machine.acc());
+        if self.machine.step().is_err() {
+            return Ok(false);
+        }
+
+        let after = self.machine.acc();
+        if self.watch && after != before {
+            writeln!(out, "acc: {} -> {} at {}", before, after, address)?;
+        }
+        Ok(true)
+    }
+
+    fn patch<W: Write>(&mut self, address: usize, instruction: Instruction, out: &mut W) -> Result<(), Error> {
+        let old = self.machine.program()[address];
+        self.machine.patch(address, instruction);
+        writeln!(out, "{:>5}  {} (was {})", address, instruction, old)
+    }
+
+    // Show the instruction the pointer is on, or why there isn't one
+    fn show_next<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        match self.machine.halted() {
+            Some(outcome) => writeln!(out, "Halted: {}", outcome),
+            None => {
+                let pointer = self.machine.pointer();
+                let instruction = self.machine.program()[pointer as usize];
+                writeln!(out, "=> {:>5}  {:<10} acc = {}", pointer, instruction.to_string(), self.machine.acc())
+            }
+        }
+    }
+
+    // Show `count` instructions centered on `center`. The pointer is marked with `=>`, and
+    // address breakpoints with `*`.
+    fn list<W: Write>(&self, center: usize, count: usize, out: &mut W) -> Result<(), Error> {
+        let program = self.machine.program();
+        let start = center.saturating_sub(count / 2);
+        let end = (start + count).min(program.len());
+
+        for (address, instruction) in program.iter().enumerate().take(end).skip(start) {
+            let marker = if self.machine.pointer() == address as i64 { "=>" } else { "  " };
+            let breakpoint = self.breakpoints.iter().any(|x| matches!(x, Some(Breakpoint::Address(a)) if *a == address));
+            let breakpoint = if breakpoint { "*" } else { " " };
+            writeln!(out, "{}{} {:>5}  {}", marker, breakpoint, address, instruction)?;
+        }
+        Ok(())
+    }
+
+    // Parse an address given to a command, making sure it's in the program
+    fn address(&self, address: &str) -> Result<usize, String> {
+        let len = self.machine.program().len();
+        match address.parse() {
+            Ok(x) if x < len => Ok(x),
+            Ok(x) => Err(format!("Address {} is past the end of the program ({} instructions)", x, len)),
+            Err(_) => Err(format!("'{}' is not an address", address)),
+        }
+    }
+}
+
+fn parse_number(s: &str) -> Result<usize, String> {
+    s.parse().map_err(|_| format!("'{}' is not a number", s))
+}
+
+// Put a command back together for error messages
+fn args_line(command: &str, args: &[&str]) -> String {
+    let mut words = vec![command];
+    words.extend(args);
+    words.join(" ")
+}
@@ -1,19 +1,20 @@
+use crate::assembler;
 use crate::instruction::Instruction;
+use crate::isa::Isa;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Error};
+use std::io::{BufRead, BufReader, Error, ErrorKind};
 
-// Function to read in lines from an input file and convert them to a Vec<Instruction>
-pub fn read_input(filename: &str) -> Result<Vec<Instruction>, Error> {
-    let file = File::open(&filename)?; // Open file or panic
+// Function to read in lines from an input file and assemble them into a Vec<Instruction> using
+// the opcodes in `isa`. The file can be plain puzzle input, or source with labels and comments
+// (see `assembler`).
+pub fn read_input(filename: &str, isa: &Isa) -> Result<Vec<Instruction>, Error> {
+    let file = File::open(filename)?; // Open file or panic
     let br = BufReader::new(file); // Create read buffer
-    let mut v = vec![]; // Initialize empty vector
 
-    // For each line in the input file...
-    for line in br.lines() {
-        let line_string = line?.trim().to_string();
-        let instruction = Instruction::from_string(&line_string);
-        v.push(instruction);
+    // Labels can be used before they're defined, so read the whole file first
+    let lines: Vec<String> = br.lines().collect::<Result<_, _>>()?;
+    match assembler::assemble(&lines.join("\n"), isa) {
+        Ok(v) => Ok(v), // Return data
+        Err(e) => Err(Error::new(ErrorKind::InvalidData, format!("{}: {}", filename, e))),
     }
-
-    return Ok(v); // Return data
 }
@@ -1,48 +1,113 @@
-// Struct that defines an 'instruction', i.e. a line from the input file
-#[derive(Debug)]
-pub enum Instruction {
-    ACC(i32),
-    JMP(i32),
-    NOP(i32),
+use crate::isa::{Effect, Isa, Opcode, OperandKind, MAX_OPERANDS};
+use std::fmt;
+
+// Names of the console's registers. The first one is the accumulator, the only register the boot
+// code uses.
+pub const REGISTER_NAMES: [&str; 5] = ["acc", "a", "b", "c", "d"];
+
+pub type Registers = [i32; REGISTER_NAMES.len()];
+
+// Struct that defines an 'instruction', i.e. a line from the input file: an opcode and its
+// operands, as register numbers for Register operands and as signed numbers otherwise
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction {
+    pub opcode: &'static Opcode,
+    pub args: [i32; MAX_OPERANDS],
+}
+
+// Parse a single operand of the given kind
+pub fn parse_operand(kind: OperandKind, word: &str) -> Result<i32, String> {
+    match kind {
+        OperandKind::Register => match REGISTER_NAMES.iter().position(|x| *x == word) {
+            Some(x) => Ok(x as i32),
+            None => Err(format!("Unknown register '{}', expected one of {}", word, REGISTER_NAMES.join("/"))),
+        },
+        OperandKind::Value | OperandKind::Offset => match word.parse() {
+            Ok(x) => Ok(x),
+            Err(_) => Err(format!("'{}' is not a number", word)),
+        },
+    }
+}
+
+// Write a single operand the way it's parsed, e.g. "b" or "-4"
+pub fn show_operand(kind: OperandKind, arg: i32) -> String {
+    match kind {
+        OperandKind::Register => REGISTER_NAMES[arg as usize].to_string(),
+        OperandKind::Value | OperandKind::Offset => format!("{:+}", arg),
+    }
 }
 
 impl Instruction {
-    // Create an Instruction from an input file line
-    pub fn from_string(s: &str) -> Instruction {
-        let instruction = s[..4].trim(); // The alpha part
-        let value = s[4..].trim().parse().unwrap(); // The numeric part
-
-        // Convert to Instruction based on the alpha part
-        match instruction {
-            "acc" => Instruction::ACC(value),
-            "jmp" => Instruction::JMP(value),
-            "nop" => Instruction::NOP(value),
-            _ => panic!("Can't parse Instruction from {}", s),
+    pub fn new(opcode: &'static Opcode, operands: &[i32]) -> Instruction {
+        let mut args = [0; MAX_OPERANDS];
+        args[..operands.len()].copy_from_slice(operands);
+        Instruction { opcode, args }
+    }
+
+    // Create an Instruction from a string like "jmp -4" using the opcodes in `isa`, or describe
+    // what's wrong with it
+    pub fn parse(s: &str, isa: &Isa) -> Result<Instruction, String> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (mnemonic, operands) = match words.split_first() {
+            Some(x) => x,
+            None => return Err(String::from("Expected an instruction")),
+        };
+        let opcode = match isa.get(mnemonic) {
+            Some(x) => x,
+            None => return Err(format!("Unknown operation '{}', expected one of {}", mnemonic, isa.mnemonics().join("/"))),
+        };
+        if operands.len() != opcode.operands.len() {
+            return Err(format!("{} takes {} operand(s), got {}", mnemonic, opcode.operands.len(), operands.len()));
+        }
+
+        let mut args = [0; MAX_OPERANDS];
+        for (i, (&kind, word)) in opcode.operands.iter().zip(operands).enumerate() {
+            args[i] = parse_operand(kind, word)?;
         }
+        Ok(Instruction { opcode, args })
+    }
+
+    // The operands that go with the opcode, without the unused padding
+    pub fn operands(&self) -> &[i32] {
+        &self.args[..self.opcode.operands.len()]
+    }
+
+    // The jump offset, if the opcode takes one
+    pub fn offset(&self) -> Option<i32> {
+        let i = self.opcode.operands.iter().position(|&x| x == OperandKind::Offset)?;
+        Some(self.args[i])
     }
 
     // Given a mutable reference to a line number in the instruction set `pointer` and a mutable
-    // reference to an accumulator `acc`, update `pointer` and `acc` based on the desired
-    // Instruction behavior (i.e., Instruction::ACC moves one instruction forward and adds its
-    // value to the accumulator).
-    pub fn execute(&self, pointer: &mut usize, acc: &mut i32) {
-        match self {
-            Instruction::ACC(x) => {
-                *pointer += 1;
-                *acc += x;
-            }
-            Instruction::JMP(x) => *pointer = (*pointer as i32 + x) as usize,
-            Instruction::NOP(_) => *pointer += 1,
+    // reference to the `registers`, update `pointer` and `registers` based on the opcode (i.e.,
+    // acc moves one instruction forward and adds its value to the accumulator). The pointer is
+    // signed, so that a jump off the front of the program can be caught rather than wrapping
+    // around. If the opcode halts the program, the pointer stays where it is.
+    pub fn execute(&self, pointer: &mut i64, registers: &mut Registers) -> Effect {
+        let effect = (self.opcode.run)(self.operands(), registers);
+        match effect {
+            Effect::Next => *pointer += 1,
+            Effect::Jump(x) => *pointer += i64::from(x),
+            Effect::Halt => (),
         }
+        effect
     }
 
-    // Given a mutable reference to a line number in the instruction set `pointer`, run the
-    // Instruction (JMP or NOP) as if it were the other one (NOP or JMP). Ignore ACC instructions
-    pub fn flip_execute(&self, pointer: &mut usize) {
-        match self {
-            Instruction::ACC(_) => (),
-            Instruction::JMP(x) => Instruction::NOP(*x).execute(pointer, &mut 0),
-            Instruction::NOP(x) => Instruction::JMP(*x).execute(pointer, &mut 0),
+    // The Instruction swapped for the one its opcode can be swapped for (e.g. JMP for NOP),
+    // keeping the operands. Most opcodes, like ACC, can't be swapped.
+    pub fn flipped(&self) -> Option<Instruction> {
+        Some(Instruction { opcode: self.opcode.flip?, args: self.args })
+    }
+}
+
+// Written the same way as in the input file, e.g. "jmp -4"
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut text = self.opcode.mnemonic.to_string();
+        for (&kind, &arg) in self.opcode.operands.iter().zip(self.operands()) {
+            text.push(' ');
+            text.push_str(&show_operand(kind, arg));
         }
+        f.pad(&text)
     }
 }
@@ -0,0 +1,222 @@
+// The instruction set of the console, as a table of opcodes instead of a fixed enum, so that other
+// puzzle machines can be built on the same Machine, assembler and debugger. Each `Opcode` says
+// how its operands are written, how it can move the pointer, and what it does when run; an `Isa`
+// is the set of opcodes a program may use, looked up by mnemonic when parsing. The puzzle's own
+// acc/jmp/nop are `Isa::boot_code()`.
+//
+// To add an opcode, define it as a `static Opcode` and register it:
+//
+//     static DEC: Opcode = Opcode {
+//         mnemonic: "dec",
+//         operands: &[OperandKind::Register],
+//         flow: Flow::Next,
+//         flip: None,
+//         run: |args, registers| {
+//             registers[args[0] as usize] = registers[args[0] as usize].wrapping_sub(1);
+//             Effect::Next
+//         },
+//     };
+//
+//     let isa = Isa::boot_code().with(&DEC);
+
+use crate::instruction::Registers;
+use std::fmt;
+
+// Most operands an opcode can take
+pub const MAX_OPERANDS: usize = 2;
+
+// The kinds of operand an opcode can take
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperandKind {
+    Register, // A register name, e.g. "acc" or "b"
+    Value,    // A signed number, e.g. "+4"
+    Offset,   // A signed number of instructions to jump by, or a label in assembler source
+}
+
+// How an opcode can move the pointer, so programs can be analysed without running them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flow {
+    Next,   // Always on to the next instruction
+    Jump,   // Always by its Offset operand
+    Branch, // Either of those, depending on the registers
+    Halt,   // Stops the program
+}
+
+// What running an instruction did to the pointer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    Next,
+    Jump(i32),
+    Halt,
+}
+
+// A single operation. `run` gets the instruction's operands in order (register numbers for
+// Register operands) and the registers to update.
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    pub operands: &'static [OperandKind],
+    pub flow: Flow,
+    pub flip: Option<&'static Opcode>, // The opcode it can be swapped for when repairing a program
+    pub run: fn(&[i32], &mut Registers) -> Effect,
+}
+
+// Only the mnemonic, since `flip` can refer back to the same opcode
+impl fmt::Debug for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Opcode({})", self.mnemonic)
+    }
+}
+
+// --- The boot code instruction set ---
+
+// acc +N: add N to the accumulator
+pub static ACC: Opcode = Opcode {
+    mnemonic: "acc",
+    operands: &[OperandKind::Value],
+    flow: Flow::Next,
+    flip: None,
+    run: |args, registers| {
+        registers[0] += args[0];
+        Effect::Next
+    },
+};
+
+// jmp +N: jump N instructions
+pub static JMP: Opcode = Opcode {
+    mnemonic: "jmp",
+    operands: &[OperandKind::Offset],
+    flow: Flow::Jump,
+    flip: Some(&NOP),
+    run: |args, _| Effect::Jump(args[0]),
+};
+
+// nop +N: do nothing. The offset is only there for when it turns out to be a corrupted jmp.
+pub static NOP: Opcode = Opcode {
+    mnemonic: "nop",
+    operands: &[OperandKind::Offset],
+    flow: Flow::Next,
+    flip: Some(&JMP),
+    run: |_, _| Effect::Next,
+};
+
+// --- Extra opcodes for other machines ---
+
+// hlt: stop the program, the same as running off the end of it
+pub static HLT: Opcode = Opcode {
+    mnemonic: "hlt",
+    operands: &[],
+    flow: Flow::Halt,
+    flip: None,
+    run: |_, _| Effect::Halt,
+};
+
+// set r +N: put N in register r
+pub static SET: Opcode = Opcode {
+    mnemonic: "set",
+    operands: &[OperandKind::Register, OperandKind::Value],
+    flow: Flow::Next,
+    flip: None,
+    run: |args, registers| {
+        registers[args[0] as usize] = args[1];
+        Effect::Next
+    },
+};
+
+// cpy r s: copy register s into register r
+pub static CPY: Opcode = Opcode {
+    mnemonic: "cpy",
+    operands: &[OperandKind::Register, OperandKind::Register],
+    flow: Flow::Next,
+    flip: None,
+    run: |args, registers| {
+        registers[args[0] as usize] = registers[args[1] as usize];
+        Effect::Next
+    },
+};
+
+// add r +N: add N to register r
+pub static ADD: Opcode = Opcode {
+    mnemonic: "add",
+    operands: &[OperandKind::Register, OperandKind::Value],
+    flow: Flow::Next,
+    flip: None,
+    run: |args, registers| {
+        let r = args[0] as usize;
+        registers[r] = registers[r].wrapping_add(args[1]);
+        Effect::Next
+    },
+};
+
+// mul r +N: multiply register r by N
+pub static MUL: Opcode = Opcode {
+    mnemonic: "mul",
+    operands: &[OperandKind::Register, OperandKind::Value],
+    flow: Flow::Next,
+    flip: None,
+    run: |args, registers| {
+        let r = args[0] as usize;
+        registers[r] = registers[r].wrapping_mul(args[1]);
+        Effect::Next
+    },
+};
+
+// jnz r +N: jump N instructions if register r isn't zero
+pub static JNZ: Opcode = Opcode {
+    mnemonic: "jnz",
+    operands: &[OperandKind::Register, OperandKind::Offset],
+    flow: Flow::Branch,
+    flip: Some(&JZ),
+    run: |args, registers| if registers[args[0] as usize] != 0 { Effect::Jump(args[1]) } else { Effect::Next },
+};
+
+// jz r +N: jump N instructions if register r is zero
+pub static JZ: Opcode = Opcode {
+    mnemonic: "jz",
+    operands: &[OperandKind::Register, OperandKind::Offset],
+    flow: Flow::Branch,
+    flip: Some(&JNZ),
+    run: |args, registers| if registers[args[0] as usize] == 0 { Effect::Jump(args[1]) } else { Effect::Next },
+};
+
+// The set of opcodes a program can use
+#[derive(Debug, Clone, Default)]
+pub struct Isa {
+    opcodes: Vec<&'static Opcode>,
+}
+
+impl Isa {
+    // The puzzle's instruction set: acc, jmp and nop
+    pub fn boot_code() -> Isa {
+        Isa::default().with(&ACC).with(&JMP).with(&NOP)
+    }
+
+    // The boot code plus halting, general purpose registers and conditional jumps
+    pub fn extended() -> Isa {
+        Isa::boot_code().with(&HLT).with(&SET).with(&CPY).with(&ADD).with(&MUL).with(&JNZ).with(&JZ)
+    }
+
+    // Gets the instruction set for a name given on the command line
+    pub fn from_name(name: &str) -> Option<Isa> {
+        match name {
+            "boot" => Some(Isa::boot_code()),
+            "extended" => Some(Isa::extended()),
+            _ => None,
+        }
+    }
+
+    // Add `opcode`, replacing any opcode already registered with the same mnemonic
+    pub fn with(mut self, opcode: &'static Opcode) -> Isa {
+        self.opcodes.retain(|x| x.mnemonic != opcode.mnemonic);
+        self.opcodes.push(opcode);
+        self
+    }
+
+    // Look up an opcode by its mnemonic
+    pub fn get(&self, mnemonic: &str) -> Option<&'static Opcode> {
+        self.opcodes.iter().find(|x| x.mnemonic == mnemonic).copied()
+    }
+
+    pub fn mnemonics(&self) -> Vec<&'static str> {
+        self.opcodes.iter().map(|x| x.mnemonic).collect()
+    }
+}
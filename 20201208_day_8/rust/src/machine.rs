@@ -0,0 +1,200 @@
+use crate::instruction::{Instruction, Registers};
+use crate::isa::{Effect, Flow};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+// Most addresses kept in the history of instructions run
+pub const HISTORY_LIMIT: usize = 1000;
+
+// Step limit for a program with conditional jumps when none is given, since every state seen is
+// kept to spot loops, and a long enough run would otherwise fill up memory
+pub const DEFAULT_BRANCH_STEP_LIMIT: usize = 1_000_000;
+
+// How a run of the program ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    // Tried to run the instruction just past the end of the program, the normal way to finish,
+    // or ran an instruction that halts the program
+    Terminated { acc: i32 },
+
+    // Was about to run the instruction at `at` for a second time, with `acc` in the accumulator
+    InfiniteLoop { at: usize, acc: i32 },
+
+    // Jumped somewhere other than an instruction or the end of the program
+    OutOfBounds { pointer: i64 },
+
+    // Ran `steps` instructions without finishing
+    StepLimit { steps: usize },
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Terminated { acc } => write!(f, "terminated with acc {}", acc),
+            Outcome::InfiniteLoop { at, acc } => {
+                write!(f, "infinite loop, about to run {} again with acc {}", at, acc)
+            }
+            Outcome::OutOfBounds { pointer } => write!(f, "jumped out of bounds to {}", pointer),
+            Outcome::StepLimit { steps } => write!(f, "hit the step limit of {} steps", steps),
+        }
+    }
+}
+
+// The handheld console: a program, a pointer to the next instruction to run and the registers,
+// the first of which is the accumulator. Running the program stops at the first instruction that
+// would be run for a second time, since with nothing else changing between visits, the program
+// would repeat itself forever from there. That only holds if the pointer never depends on the
+// registers, though; with conditional jumps in the program, it takes the pointer and every
+// register coming back around to the same values to be sure of a loop.
+#[derive(Debug, Clone)]
+pub struct Machine {
+    program: Vec<Instruction>,
+    pointer: i64,
+    registers: Registers,
+    stopped: bool, // Ran an instruction that halts the program
+    visited: Vec<bool>,
+    states: Option<HashSet<(usize, Registers)>>, // Only kept if the program has conditional jumps
+    steps: usize,
+    history: VecDeque<usize>, // The last `HISTORY_LIMIT` addresses run
+    step_limit: Option<usize>,
+}
+
+impl Machine {
+    pub fn new(program: Vec<Instruction>) -> Machine {
+        let visited = vec![false; program.len()];
+        let mut machine = Machine {
+            program,
+            pointer: 0,
+            registers: Registers::default(),
+            stopped: false,
+            visited,
+            states: None,
+            steps: 0,
+            history: VecDeque::new(),
+            step_limit: None,
+        };
+        machine.reset();
+        machine
+    }
+
+    // Stop any run after `limit` instructions. Without this, a program with conditional jumps
+    // stops after `DEFAULT_BRANCH_STEP_LIMIT` instructions, and any other program runs until it
+    // halts or loops, which takes at most one step per instruction.
+    pub fn with_step_limit(mut self, limit: usize) -> Machine {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    // Put the pointer and registers back to the start, and forget every instruction run so far
+    pub fn reset(&mut self) {
+        self.pointer = 0;
+        self.registers = Registers::default();
+        self.stopped = false;
+        self.visited.iter_mut().for_each(|x| *x = false);
+        self.states = None;
+        self.watch_states();
+        self.steps = 0;
+        self.history.clear();
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    // Address of the next instruction to run. Can be negative or past the end of the program
+    // after a bad jump.
+    pub fn pointer(&self) -> i64 {
+        self.pointer
+    }
+
+    pub fn acc(&self) -> i32 {
+        self.registers[0]
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    // Number of instructions run so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // Addresses of the last instructions run, up to `HISTORY_LIMIT` of them, in the order they
+    // were run
+    pub fn history(&self) -> &VecDeque<usize> {
+        &self.history
+    }
+
+    // Replace the instruction at `address`
+    pub fn patch(&mut self, address: usize, instruction: Instruction) {
+        self.program[address] = instruction;
+        self.watch_states();
+    }
+
+    // Start or stop keeping every state seen, depending on whether the program has any
+    // conditional jumps. States are only kept from the point the first one is added.
+    fn watch_states(&mut self) {
+        let branches = self.program.iter().any(|x| x.opcode.flow == Flow::Branch);
+        match (branches, &self.states) {
+            (true, None) => self.states = Some(HashSet::new()),
+            (false, Some(_)) => self.states = None,
+            _ => (),
+        }
+    }
+
+    // If the program can't go on from where the pointer is, the reason why
+    pub fn halted(&self) -> Option<Outcome> {
+        let len = self.program.len() as i64;
+        if self.stopped || self.pointer == len {
+            return Some(Outcome::Terminated { acc: self.acc() });
+        }
+        if self.pointer < 0 || self.pointer > len {
+            return Some(Outcome::OutOfBounds { pointer: self.pointer });
+        }
+        let at = self.pointer as usize;
+        let repeated = match &self.states {
+            Some(states) => states.contains(&(at, self.registers)),
+            None => self.visited[at],
+        };
+        if repeated {
+            return Some(Outcome::InfiniteLoop { at, acc: self.acc() });
+        }
+        let default_limit = self.states.as_ref().map(|_| DEFAULT_BRANCH_STEP_LIMIT);
+        if self.step_limit.or(default_limit).is_some_and(|limit| self.steps >= limit) {
+            return Some(Outcome::StepLimit { steps: self.steps });
+        }
+        None
+    }
+
+    // Run the next instruction, unless the program has halted, in which case report why
+    pub fn step(&mut self) -> Result<(), Outcome> {
+        if let Some(outcome) = self.halted() {
+            return Err(outcome);
+        }
+
+        let at = self.pointer as usize;
+        self.visited[at] = true;
+        if let Some(states) = &mut self.states {
+            states.insert((at, self.registers));
+        }
+        self.steps += 1;
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(at);
+        if self.program[at].execute(&mut self.pointer, &mut self.registers) == Effect::Halt {
+            self.stopped = true;
+        }
+        Ok(())
+    }
+
+    // Run instructions until the program halts
+    pub fn run(&mut self) -> Outcome {
+        loop {
+            if let Err(outcome) = self.step() {
+                return outcome;
+            }
+        }
+    }
+}
@@ -110,18 +110,31 @@
 // Fix the program so that it terminates normally by changing exactly one jmp (to nop) or nop (to
 // jmp). What is the value of the accumulator after the program terminates?
 
+mod assembler;
+mod debugger;
 mod fileio;
 mod instruction;
+mod isa;
+mod machine;
 mod part_one;
 mod part_two;
+mod repair;
+mod trace;
 
+use debugger::Debugger;
 use instruction::Instruction;
+use isa::Isa;
+use machine::Machine;
+use std::env;
+use std::fs::File;
+use std::io::{self, Write};
 use std::time::Instant;
+use trace::{Format, Trace};
 
 // Timing function, given the function to run and the input arguments, runs
 // the function with the given input and prints the time to run to the
 // console
-fn time_it(f: fn(&Vec<Instruction>) -> (), instructions: &Vec<Instruction>) {
+fn time_it(f: fn(&[Instruction]), instructions: &[Instruction]) {
     let start = Instant::now();
     f(instructions);
     let duration = start.elapsed();
@@ -129,13 +142,111 @@ fn time_it(f: fn(&Vec<Instruction>) -> (), instructions: &Vec<Instruction>) {
     println!("Solved in: {:?}\n", duration);
 }
 
+// Get the value following the flag `name` in the command line arguments
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let pos = args.iter().position(|arg| arg == name)?;
+    match args.get(pos + 1) {
+        Some(x) => Some(x),
+        None => panic!("Expected a value after {}", name),
+    }
+}
+
+// Write with `write`, to the file given with `--output <file>` or to the console
+fn write_output(args: &[String], write: impl Fn(&mut dyn Write) -> Result<(), io::Error>) {
+    let result = match arg_value(args, "--output") {
+        Some(filename) => File::create(filename).and_then(|mut file| write(&mut file)),
+        None => write(&mut io::stdout().lock()),
+    };
+    if let Err(e) = result {
+        panic!("Error: {}", e);
+    }
+}
+
 fn main() {
-    // Read and parse the input file
-    let input = match fileio::read_input("../input.txt") {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Read and assemble the input file, or the program file given with `--program <file>`.
+    // Programs use the puzzle's instruction set unless `--isa extended` is given.
+    let isa = match arg_value(&args, "--isa").map(Isa::from_name) {
+        Some(Some(x)) => x,
+        Some(None) => panic!("Error: --isa should be 'boot' or 'extended'"),
+        None => Isa::boot_code(),
+    };
+    let filename = arg_value(&args, "--program").unwrap_or("../input.txt");
+    let input = match fileio::read_input(filename, &isa) {
         Ok(x) => x,
         Err(e) => panic!("Error: {}", e),
     };
 
+    // With `--disassemble`, write the program as source with labels, and with `--assemble`, as
+    // plain puzzle input, to the console or the file given with `--output <file>`
+    if args.iter().any(|x| x == "--disassemble") {
+        write_output(&args, |mut out| assembler::write_source(&input, &mut out));
+        return;
+    }
+    if args.iter().any(|x| x == "--assemble") {
+        write_output(&args, |mut out| assembler::write_program(&input, &mut out));
+        return;
+    }
+
+    // The flags below run the program as it is instead of solving the puzzle. `--flip <addr>`
+    // swaps the jmp or nop at that address first, e.g. to compare the corrupted program with the
+    // repaired one, and `--step-limit <n>` stops the run after that many instructions. Programs
+    // with conditional jumps stop after a million instructions unless given a step limit.
+    let mut machine = Machine::new(input.clone());
+    if let Some(address) = arg_value(&args, "--flip") {
+        let address: usize = match address.parse() {
+            Ok(x) => x,
+            Err(_) => panic!("Error: '{}' is not an address", address),
+        };
+        match input.get(address).and_then(|x| x.flipped()) {
+            Some(flipped) => machine.patch(address, flipped),
+            None => panic!("Error: no jmp or nop at address {}", address),
+        }
+    }
+    if let Some(limit) = arg_value(&args, "--step-limit") {
+        match limit.parse() {
+            Ok(x) => machine = machine.with_step_limit(x),
+            Err(_) => panic!("Error: --step-limit needs a number of steps"),
+        }
+    }
+    let format = match arg_value(&args, "--format").map(Format::from_name) {
+        Some(Some(x)) => x,
+        Some(None) => panic!("Error: --format should be 'text' or 'json'"),
+        None => Format::Text,
+    };
+
+    // With `--run`, report how the program ended
+    if args.iter().any(|x| x == "--run") {
+        let outcome = machine.run();
+        println!("Program {} after {} steps", outcome, machine.steps());
+        return;
+    }
+
+    // With `--debug`, step through the program interactively
+    if args.iter().any(|x| x == "--debug") {
+        let stdin = io::stdin();
+        let mut debugger = Debugger::new(machine, isa);
+        if let Err(e) = debugger.run(stdin.lock(), &mut io::stdout().lock()) {
+            panic!("Error: {}", e);
+        }
+        return;
+    }
+
+    // With `--trace`, write every step of the run, and with `--profile`, how many times each
+    // instruction ran. Both are written as `--format text` (the default) or `--format json`, to
+    // the console or the file given with `--output <file>`.
+    if args.iter().any(|x| x == "--trace") {
+        let trace = Trace::record(&mut machine);
+        write_output(&args, |mut out| trace.write_steps(format, &mut out));
+        return;
+    }
+    if args.iter().any(|x| x == "--profile") {
+        let trace = Trace::record(&mut machine);
+        write_output(&args, |mut out| trace.write_profile(machine.program(), format, &mut out));
+        return;
+    }
+
     // Run the parts and report the result and time taken
     time_it(part_one::solve, &input); // 1217
     time_it(part_two::solve, &input); // 501
@@ -1,20 +1,11 @@
 use crate::instruction::Instruction;
-use std::collections::HashSet;
+use crate::machine::{Machine, Outcome};
 
-// Iterate through the instructions and run them until hitting an instruction that has been run
-// before, then report the accumulator value
-pub fn solve(instructions: &Vec<Instruction>) {
-    let mut pointer = 0;
-    let mut acc = 0;
-    let mut pointer_history = HashSet::new();
-
-    loop {
-        if pointer_history.insert(pointer) {
-            instructions[pointer].execute(&mut pointer, &mut acc);
-        } else {
-            break;
-        }
+// Run the instructions until hitting an instruction that has been run before, then report the
+// accumulator value
+pub fn solve(instructions: &[Instruction]) {
+    match Machine::new(instructions.to_vec()).run() {
+        Outcome::InfiniteLoop { acc, .. } => println!("The answer to part one is {}", acc),
+        outcome => println!("The program didn't loop: {:?}", outcome),
     }
-
-    println!("The answer to part one is {}", acc);
 }
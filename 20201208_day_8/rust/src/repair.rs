@@ -0,0 +1,228 @@
+use crate::instruction::Instruction;
+use crate::isa::{self, Flow};
+use crate::machine::{Machine, Outcome};
+use crate::trace::Trace;
+use std::ptr;
+
+// A single flipped instruction that lets the program terminate
+#[derive(Debug, Clone, Copy)]
+pub struct Fix {
+    pub address: usize,
+    pub instruction: Instruction, // The instruction to put at `address`
+    pub acc: i32,                 // The accumulator when the repaired program terminates
+}
+
+// Address of the instruction that runs after the one at `address`, where the address just past
+// the end of the program means termination, as does any instruction that halts the program.
+// None for a jump anywhere else. Only meaningful for opcodes without conditional jumps.
+fn successor(program: &[Instruction], address: usize, instruction: Instruction) -> Option<usize> {
+    let pointer = match instruction.opcode.flow {
+        Flow::Next | Flow::Branch => address as i64 + 1,
+        Flow::Jump => address as i64 + i64::from(instruction.offset()?),
+        Flow::Halt => return Some(program.len()),
+    };
+    if (0..=program.len() as i64).contains(&pointer) {
+        Some(pointer as usize)
+    } else {
+        None
+    }
+}
+
+// Every address from which running the program reaches termination, starting with the address
+// just past the end and with each address after its successor. Every instruction has exactly one
+// successor in the control flow graph, so walking the edges backwards from the end of the program
+// finds each of these addresses once.
+fn walk_back_from_end(program: &[Instruction]) -> Vec<usize> {
+    let end = program.len();
+    let mut predecessors = vec![Vec::new(); end + 1];
+    for (address, &instruction) in program.iter().enumerate() {
+        if let Some(next) = successor(program, address, instruction) {
+            predecessors[next].push(address);
+        }
+    }
+
+    let mut order = vec![end];
+    let mut stack = vec![end];
+    while let Some(next) = stack.pop() {
+        for &address in &predecessors[next] {
+            order.push(address);
+            stack.push(address);
+        }
+    }
+
+    order
+}
+
+// What running the instruction adds to the accumulator, if that's the same whatever is in the
+// registers. None for any opcode that could do something else, like `set acc +1`.
+fn acc_delta(instruction: Instruction) -> Option<i32> {
+    let opcode = instruction.opcode;
+    if ptr::eq(opcode, &isa::ACC) {
+        Some(instruction.args[0])
+    } else if [&isa::JMP, &isa::NOP, &isa::HLT].iter().any(|&x| ptr::eq(opcode, x)) {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+// For each address in `order` (see `walk_back_from_end()`), what running the program from there
+// adds to the accumulator by the time it terminates. None if any instruction on the way changes
+// the accumulator by something other than a fixed amount.
+fn acc_to_end(program: &[Instruction], order: &[usize]) -> Option<Vec<i32>> {
+    let mut to_end = vec![0; program.len() + 1];
+    for &address in order.iter().skip(1) {
+        let instruction = program[address];
+        let next = successor(program, address, instruction)?;
+        to_end[address] = acc_delta(instruction)?.wrapping_add(to_end[next]);
+    }
+    Some(to_end)
+}
+
+// Every single flip (e.g. JMP to NOP) that makes the program terminate, in the order the broken
+// program reaches them. Only instructions the broken program actually runs can matter, and
+// flipping one of those fixes the program exactly when its new successor reaches the end: none of
+// the addresses that reach the end are run by the broken program, so the repaired program can't
+// find its way back to the flip. The final accumulator is then the accumulator before the flip,
+// plus what the flipped instruction and the rest of the way to the end add to it, so finding the
+// fixes is linear in the length of the program. Programs with opcodes that can change the
+// accumulator by anything other than a fixed amount have each fix run instead. Fails if the
+// program already terminates, or if it has conditional jumps, which make the control flow depend
+// on the registers.
+pub fn find_fixes(program: &[Instruction]) -> Result<Vec<Fix>, String> {
+    if let Some(x) = program.iter().find(|x| x.opcode.flow == Flow::Branch) {
+        return Err(format!("Can't repair a program with conditional jumps like '{}'", x));
+    }
+
+    let mut machine = Machine::new(program.to_vec());
+    let trace = Trace::record(&mut machine);
+    if let Outcome::Terminated { .. } = trace.outcome {
+        return Err(String::from("The program already terminates"));
+    }
+
+    let order = walk_back_from_end(program);
+    let mut reached = vec![false; program.len() + 1];
+    for &address in &order {
+        reached[address] = true;
+    }
+    let to_end = acc_to_end(program, &order);
+
+    let mut fixes = Vec::new();
+    for step in &trace.steps {
+        let flipped = match step.instruction.flipped() {
+            Some(x) => x,
+            None => continue,
+        };
+        let next = match successor(program, step.pointer, flipped) {
+            Some(x) if reached[x] => x,
+            _ => continue,
+        };
+
+        let acc = match (&to_end, acc_delta(flipped)) {
+            (Some(to_end), Some(delta)) => step.acc_before.wrapping_add(delta).wrapping_add(to_end[next]),
+            _ => {
+                machine.reset();
+                machine.patch(step.pointer, flipped);
+                let outcome = machine.run();
+                machine.patch(step.pointer, step.instruction);
+                match outcome {
+                    Outcome::Terminated { acc } => acc,
+                    _ => continue,
+                }
+            }
+        };
+        fixes.push(Fix { address: step.pointer, instruction: flipped, acc });
+    }
+
+    Ok(fixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isa::Isa;
+
+    fn parse(lines: &[&str], isa: &Isa) -> Vec<Instruction> {
+        lines.iter().map(|x| Instruction::parse(x, isa).unwrap()).collect()
+    }
+
+    // A program that loops forever, where every one of its `flips` NOPs can be flipped to jump
+    // out of the loop and into a run of `acc +2` instructions that ends the program. NOP `i`
+    // follows `acc +1` `i + 1` times, and jumps `i % exit_len` instructions into the run. The JMP
+    // that closes the loop can be flipped too, to fall through to the start of the run.
+    fn many_fixes(flips: usize, exit_len: usize) -> Vec<Instruction> {
+        let exit = 2 * flips + 1;
+        let mut program = Vec::new();
+        for i in 0..flips {
+            let nop = program.len() + 1;
+            program.push(Instruction::new(&isa::ACC, &[1]));
+            program.push(Instruction::new(&isa::NOP, &[(exit + i % exit_len) as i32 - nop as i32]));
+        }
+        program.push(Instruction::new(&isa::JMP, &[-(2 * flips as i32)]));
+        program.extend((0..exit_len).map(|_| Instruction::new(&isa::ACC, &[2])));
+        program
+    }
+
+    // Every fix found by flipping each instruction in turn and running the whole program
+    fn fixes_by_running(program: &[Instruction]) -> Vec<(usize, i32)> {
+        let mut fixes = Vec::new();
+        for (address, instruction) in program.iter().enumerate() {
+            if let Some(flipped) = instruction.flipped() {
+                let mut machine = Machine::new(program.to_vec());
+                machine.patch(address, flipped);
+                if let Outcome::Terminated { acc } = machine.run() {
+                    fixes.push((address, acc));
+                }
+            }
+        }
+        fixes
+    }
+
+    fn sorted(fixes: &[Fix]) -> Vec<(usize, i32)> {
+        let mut fixes: Vec<(usize, i32)> = fixes.iter().map(|x| (x.address, x.acc)).collect();
+        fixes.sort();
+        fixes
+    }
+
+    #[test]
+    fn fixes_puzzle_example() {
+        let program = parse(
+            &["nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3", "acc -99", "acc +1", "jmp -4", "acc +6"],
+            &Isa::boot_code(),
+        );
+        let fixes = find_fixes(&program).unwrap();
+        assert_eq!(sorted(&fixes), vec![(7, 8)]);
+    }
+
+    #[test]
+    fn matches_running_each_fix() {
+        let program = many_fixes(300, 70);
+        assert_eq!(sorted(&find_fixes(&program).unwrap()), fixes_by_running(&program));
+    }
+
+    // Re-running each of these fixes would take hundreds of millions of steps
+    #[test]
+    fn many_fixes_in_a_large_program() {
+        let (flips, exit_len) = (20_000, 10_000);
+        let fixes = find_fixes(&many_fixes(flips, exit_len)).unwrap();
+        assert_eq!(fixes.len(), flips + 1);
+        for (i, fix) in fixes[..flips].iter().enumerate() {
+            assert_eq!(fix.address, 2 * i + 1);
+            assert_eq!(fix.acc, (i + 1 + 2 * (exit_len - i % exit_len)) as i32);
+        }
+        assert_eq!(fixes[flips].address, 2 * flips);
+        assert_eq!(fixes[flips].acc, (flips + 2 * exit_len) as i32);
+    }
+
+    #[test]
+    fn runs_fixes_that_set_the_accumulator() {
+        let program = parse(&["set acc +5", "nop +2", "jmp -1", "mul acc +3"], &Isa::extended());
+        assert_eq!(sorted(&find_fixes(&program).unwrap()), vec![(1, 15), (2, 15)]);
+    }
+
+    #[test]
+    fn conditional_jumps_are_an_error() {
+        let program = parse(&["set a +1", "jnz a +0"], &Isa::extended());
+        assert!(find_fixes(&program).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::instruction::Instruction;
+use crate::machine::{Machine, Outcome};
+use std::io::{Error, Write};
+
+// The ways a trace or profile can be written
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Text, // Aligned columns, one line per step or instruction
+    Json, // An object with the outcome and an array of steps or instructions
+}
+
+impl Format {
+    // Gets the format for a name given on the command line
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+// A single instruction run by the machine, and what it did to the accumulator
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub step: usize, // Counting from 1
+    pub pointer: usize,
+    pub instruction: Instruction,
+    pub acc_before: i32,
+    pub acc_after: i32,
+}
+
+// Every step of a run, in order, and how the run ended
+#[derive(Debug)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+    pub outcome: Outcome,
+    program_len: usize,
+}
+
+impl Trace {
+    // Run `machine` until it halts, recording each step along the way
+    pub fn record(machine: &mut Machine) -> Trace {
+        let mut steps = Vec::new();
+
+        loop {
+            let (pointer, acc_before) = (machine.pointer(), machine.acc());
+            if let Err(outcome) = machine.step() {
+                let program_len = machine.program().len();
+                return Trace { steps, outcome, program_len };
+            }
+
+            // A step only happens when the pointer was on an instruction
+            let pointer = pointer as usize;
+            steps.push(Step {
+                step: steps.len() + 1,
+                pointer,
+                instruction: machine.program()[pointer],
+                acc_before,
+                acc_after: machine.acc(),
+            });
+        }
+    }
+
+    // Number of times each instruction in the program was run, indexed by address
+    pub fn hit_counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.program_len];
+        for step in &self.steps {
+            counts[step.pointer] += 1;
+        }
+        counts
+    }
+
+    // Write every step of the run, followed by how it ended
+    pub fn write_steps<W: Write>(&self, format: Format, out: &mut W) -> Result<(), Error> {
+        match format {
+            Format::Text => {
+                writeln!(out, "{:>6} {:>6}  {:<10} {:>8} {:>8}", "step", "addr", "instr", "acc", "acc'")?;
+                for step in &self.steps {
+                    writeln!(
+                        out,
+                        "{:>6} {:>6}  {:<10} {:>8} {:>8}",
+                        step.step,
+                        step.pointer,
+                        step.instruction.to_string(),
+                        step.acc_before,
+                        step.acc_after
+                    )?;
+                }
+                writeln!(out, "Program {}", self.outcome)?;
+            }
+            Format::Json => {
+                writeln!(out, "{{")?;
+                writeln!(out, "  \"outcome\": {},", json_outcome(&self.outcome))?;
+                writeln!(out, "  \"steps\": [")?;
+                for (i, step) in self.steps.iter().enumerate() {
+                    let comma = if i + 1 < self.steps.len() { "," } else { "" };
+                    writeln!(
+                        out,
+                        "    {{\"step\": {}, \"pointer\": {}, \"instruction\": \"{}\", \"acc_before\": {}, \"acc_after\": {}}}{}",
+                        step.step, step.pointer, step.instruction, step.acc_before, step.acc_after, comma
+                    )?;
+                }
+                writeln!(out, "  ]")?;
+                writeln!(out, "}}")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Write how many times each instruction of `program` was run, including those never run
+    pub fn write_profile<W: Write>(
+        &self,
+        program: &[Instruction],
+        format: Format,
+        out: &mut W,
+    ) -> Result<(), Error> {
+        let counts = self.hit_counts();
+
+        match format {
+            Format::Text => {
+                writeln!(out, "{:>6}  {:<10} {:>6}", "addr", "instr", "hits")?;
+                for (address, (instruction, hits)) in program.iter().zip(&counts).enumerate() {
+                    writeln!(out, "{:>6}  {:<10} {:>6}", address, instruction.to_string(), hits)?;
+                }
+                let run = counts.iter().filter(|&&x| x > 0).count();
+                writeln!(out, "{} of {} instructions run, {} steps", run, program.len(), self.steps.len())?;
+                writeln!(out, "Program {}", self.outcome)?;
+            }
+            Format::Json => {
+                writeln!(out, "{{")?;
+                writeln!(out, "  \"outcome\": {},", json_outcome(&self.outcome))?;
+                writeln!(out, "  \"instructions\": [")?;
+                for (address, (instruction, hits)) in program.iter().zip(&counts).enumerate() {
+                    let comma = if address + 1 < program.len() { "," } else { "" };
+                    writeln!(
+                        out,
+                        "    {{\"pointer\": {}, \"instruction\": \"{}\", \"hits\": {}}}{}",
+                        address, instruction, hits, comma
+                    )?;
+                }
+                writeln!(out, "  ]")?;
+                writeln!(out, "}}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The outcome of a run as a JSON object, e.g. {"kind": "terminated", "acc": 8}
+fn json_outcome(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Terminated { acc } => format!("{{\"kind\": \"terminated\", \"acc\": {}}}", acc),
+        Outcome::InfiniteLoop { at, acc } => {
+            format!("{{\"kind\": \"infinite_loop\", \"at\": {}, \"acc\": {}}}", at, acc)
+        }
+        Outcome::OutOfBounds { pointer } => {
+            format!("{{\"kind\": \"out_of_bounds\", \"pointer\": {}}}", pointer)
+        }
+        Outcome::StepLimit { steps } => format!("{{\"kind\": \"step_limit\", \"steps\": {}}}", steps),
+    }
+}
@@ -3,19 +3,23 @@ use std::io::{BufRead, BufReader, Error, ErrorKind};
 
 // Function to read in lines from an input file and convert them to a Vec<usize>
 pub fn read_input(filename: &str) -> Result<Vec<usize>, Error> {
-    let file = File::open(&filename)?; // Open file or panic
+    let file = File::open(filename)?; // Open file or panic
     let br = BufReader::new(file); // Create read buffer
     let mut v = vec![]; // Initialize empty vector
 
     // For each line in the input file...
-    for line in br.lines() {
-        v.push(
-            line?
-                .trim()
-                .parse()
-                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?,
-        );
+    for num in read_stream(br) {
+        v.push(num?);
     }
 
-    return Ok(v); // Return data
+    Ok(v) // Return data
+}
+
+// Function to lazily read one number per line from `reader`, for input that may never end.
+// Blank lines are skipped.
+pub fn read_stream<R: BufRead>(reader: R) -> impl Iterator<Item = Result<usize, Error>> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(x) if x.trim().is_empty()))
+        .map(|line| line?.trim().parse().map_err(|e| Error::new(ErrorKind::InvalidData, e)))
 }
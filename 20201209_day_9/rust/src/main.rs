@@ -108,25 +108,79 @@
 mod fileio;
 mod part_one;
 mod part_two;
+mod validator;
 
+use std::env;
+use std::io::{self, Write};
 use std::time::Instant;
 
-// Timing function, given the function to run and the input arguments, runs
-// the function with the given input and prints the time to run to the
-// console
-fn time_it(f: fn(&Vec<usize>, usize) -> (), nums: &Vec<usize>, preamble: usize) {
+// Timing function, given the function to run, runs the function, prints the
+// time to run to the console and passes on the function's result
+fn time_it<T>(f: impl FnOnce() -> T) -> T {
     let start = Instant::now();
-    f(nums, preamble);
+    let result = f();
     println!("Solved in: {:?}\n", start.elapsed());
+    result
+}
+
+// Get the value following the flag `name` in the command line arguments
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let pos = args.iter().position(|arg| arg == name)?;
+    match args.get(pos + 1) {
+        Some(x) => Some(x),
+        None => panic!("Expected a value after {}", name),
+    }
 }
 
 fn main() {
+    // Each number is checked against the 25 before it, or as many as given with `--preamble <n>`
+    let args: Vec<String> = env::args().skip(1).collect();
+    let preamble = match arg_value(&args, "--preamble").map(|x| x.parse()) {
+        Some(Ok(x)) => x,
+        Some(Err(_)) => panic!("Error: --preamble needs a number of numbers"),
+        None => 25,
+    };
+
+    // With `--stream`, read numbers from standard input instead of the input file, and report
+    // each invalid number as soon as it arrives
+    if args.iter().any(|x| x == "--stream") {
+        let stdin = io::stdin();
+        let nums = fileio::read_stream(stdin.lock()).map(|x| match x {
+            Ok(x) => x,
+            Err(e) => panic!("Error: {}", e),
+        });
+        let mut out = io::stdout();
+        for (index, num) in validator::invalid_numbers(nums, preamble) {
+            if let Err(e) = writeln!(out, "Invalid number {} at index {}", num, index).and_then(|_| out.flush()) {
+                panic!("Error: {}", e);
+            }
+        }
+        return;
+    }
+
     // Read and parse the input file
     let input = match fileio::read_input("../input.txt") {
         Ok(x) => x,
         Err(e) => panic!("Error: {}", e),
     };
 
-    time_it(part_one::solve, &input, 25); // 1212510616
-    time_it(part_two::solve, &input, 25); // 171265123
+    // With `--all`, list every invalid number, and for each one, every range of two or more
+    // numbers that sums to it, instead of solving the puzzle
+    if args.iter().any(|x| x == "--all") {
+        let invalid = part_one::invalid_numbers(&input, preamble);
+        println!("{} invalid numbers", invalid.len());
+        for (index, num) in invalid {
+            let ranges = part_two::ranges_summing_to(&input, num);
+            println!("{} at index {}, {} ranges sum to it", num, index, ranges.len());
+            for range in ranges {
+                let weakness = part_two::weakness(&input[range.clone()]).unwrap();
+                println!("  indices {}..{} have weakness {}", range.start, range.end, weakness);
+            }
+        }
+        return;
+    }
+
+    // Part two needs the invalid number found in part one
+    let invalid = time_it(|| part_one::solve(&input, preamble)); // 1212510616
+    time_it(|| part_two::solve(&input, invalid)); // 171265123
 }
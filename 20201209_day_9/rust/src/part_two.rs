@@ -1,59 +1,71 @@
-/// Given a list of numbers `nums`, a target number `total`, and a starting index `i`, repeatedly
-/// check `num` for a range starting at `i` that sums to `total`, increasing the size of that
-/// range through the end of `nums`.
-fn check_index(nums: &Vec<usize>, total: usize, i: usize) -> Option<&[usize]> {
-    let mut next_i = i + 2; // Each range should include at least two numbers
+use std::ops::Range;
 
-    // Until reaching the end of `nums`...
-    while next_i <= nums.len() {
-        // Sum the numbers from `i` to `next_i`
-        let current_sum = nums[i..next_i].iter().fold(0, |total, next| total + next);
+/// Given a list of numbers `nums` and a target number `total`, find every contiguous range of at
+/// least two numbers in `nums` that sums to `total`, in order of where the ranges start. Since
+/// none of the numbers are negative, this is a single pass with two pointers: the end of the
+/// range moves forward one number at a time, and the start follows it for as long as the sum is
+/// too large. Zeros at the start of a matching range don't change the sum, so each of those
+/// starting points gives a matching range too.
+pub fn ranges_summing_to(nums: &[usize], total: usize) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut sum = 0;
 
-        if current_sum == total {
-            return Some(&nums[i..next_i]);
+    for end in 0..nums.len() {
+        sum += nums[end];
+        while sum > total && start <= end {
+            sum -= nums[start];
+            start += 1;
         }
-        if current_sum > total {
-            return None;
+        if sum != total {
+            continue;
+        }
+
+        let mut first = start;
+        while first < end {
+            ranges.push(first..(end + 1)); // Leaves out the single number at `end`
+            if nums[first] != 0 {
+                break;
+            }
+            first += 1;
         }
-        next_i += 1;
     }
 
-    None
+    ranges.sort_by_key(|x| (x.start, x.end));
+    ranges
+}
+
+/// The encryption weakness of a range of numbers: the sum of the smallest and largest of them
+pub fn weakness(range: &[usize]) -> Option<usize> {
+    Some(range.iter().min()? + range.iter().max()?)
 }
 
 /// Solve part two and report the results
-/// Given a list of numbers `nums` and the size of the preamble `preamble`, identify the first
-/// invalid number (according to the part one rules), then iterate backwards through the list of
-/// `nums` to find a range that sums to the first invalid number.
-pub fn solve(nums: &Vec<usize>, preamble: usize) {
-    let invalid_index = crate::part_one::first_invalid_index(nums, preamble);
-    let total = nums[invalid_index];
+/// Given a list of numbers `nums` and the first invalid number `invalid` found in part one, find
+/// the ranges of `nums` that sum to it. The puzzle promises exactly one; if there's more than
+/// one, list them all instead.
+pub fn solve(nums: &[usize], invalid: Option<usize>) {
+    let total = match invalid {
+        Some(x) => x,
+        None => {
+            println!("\nEvery number is valid, part two has no answer");
+            return;
+        }
+    };
 
-    // Work backwards through `nums`
-    let mut i = nums.len();
-    loop {
-        // Try to get a slice from `nums` that sums to `total` starting at `i`. If we fail on the
-        // first index, then no solution exists
-        let sum_slice = match check_index(nums, total, i) {
-            None => {
-                i = match i.checked_sub(1) {
-                    None => panic!("Could not solve part two!"),
-                    Some(x) => x,
-                };
-                continue;
+    let ranges = ranges_summing_to(nums, total);
+    match ranges.as_slice() {
+        [] => println!("\nNo range of numbers sums to {}", total),
+        [range] => {
+            let answer = weakness(&nums[range.clone()]).unwrap();
+            println!("\nThe answer to part two is {}", answer);
+        }
+        _ => {
+            println!("\n{} ranges of numbers sum to {}:", ranges.len(), total);
+            for range in &ranges {
+                let answer = weakness(&nums[range.clone()]).unwrap();
+                println!("  indices {}..{} have weakness {}", range.start, range.end, answer);
             }
-            Some(x) => x,
-        };
-
-        let min_num = sum_slice
-            .iter() // The minimum number in the list
-            .fold(total, |min, next| if next < &min { *next } else { min });
-
-        let max_num = sum_slice
-            .iter() // The maximum number in the list
-            .fold(0, |max, next| if next > &max { *next } else { max });
-
-        println!("\nThe answer to part two is {}", min_num + max_num);
-        break;
+        }
     }
 }
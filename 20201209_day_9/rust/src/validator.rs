@@ -0,0 +1,93 @@
+use std::collections::{HashMap, VecDeque};
+
+/// What a `Validator` made of the latest number
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Preamble, // Still filling the window, so there's nothing to check against
+    Valid,    // The sum of two different numbers in the window
+    Invalid,  // Not the sum of any two different numbers in the window
+}
+
+/// Checks a stream of XMAS numbers one at a time, each against the `preamble` numbers before it.
+/// Alongside the window itself, the validator keeps a count of every sum of two different values
+/// in the window. Each number that arrives adds its sums with the rest of the window, and the
+/// number that drops out of the window takes its sums with it, so checking a number is a single
+/// lookup and moving the window along is O(`preamble`), with no copying.
+#[derive(Debug)]
+pub struct Validator {
+    preamble: usize,
+    window: VecDeque<usize>,
+    sums: HashMap<usize, usize>, // Pair sum -> number of pairs in the window with that sum
+}
+
+impl Validator {
+    pub fn new(preamble: usize) -> Validator {
+        Validator {
+            preamble,
+            window: VecDeque::with_capacity(preamble + 1),
+            sums: HashMap::new(),
+        }
+    }
+
+    /// Check `num` against the window, then add it to the window
+    pub fn push(&mut self, num: usize) -> Verdict {
+        let verdict = if self.window.len() < self.preamble {
+            Verdict::Preamble
+        } else if self.sums.contains_key(&num) {
+            Verdict::Valid
+        } else {
+            Verdict::Invalid
+        };
+
+        if self.preamble == 0 {
+            return verdict; // Nothing is ever kept, so every number is invalid
+        }
+        if self.window.len() == self.preamble {
+            let oldest = self.window.pop_front().unwrap();
+            for &other in &self.window {
+                if let Some(sum) = pair_sum(oldest, other) {
+                    remove_one(&mut self.sums, sum);
+                }
+            }
+        }
+        for &other in &self.window {
+            if let Some(sum) = pair_sum(num, other) {
+                *self.sums.entry(sum).or_insert(0) += 1;
+            }
+        }
+        self.window.push_back(num);
+
+        verdict
+    }
+}
+
+/// Take one pair with the given `sum` out of the counts, forgetting the sum once none are left
+fn remove_one(sums: &mut HashMap<usize, usize>, sum: usize) {
+    if let Some(count) = sums.get_mut(&sum) {
+        *count -= 1;
+        if *count == 0 {
+            sums.remove(&sum);
+        }
+    }
+}
+
+/// The sum of two numbers that count as a pair, i.e. that have different values. A sum too large
+/// to fit can't match any number, so it isn't a pair either.
+fn pair_sum(a: usize, b: usize) -> Option<usize> {
+    if a == b {
+        return None;
+    }
+    a.checked_add(b)
+}
+
+/// Lazily check every number from `nums`, yielding the index and value of each invalid number as
+/// soon as it arrives, so `nums` can be an unending stream
+pub fn invalid_numbers<I>(nums: I, preamble: usize) -> impl Iterator<Item = (usize, usize)>
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut validator = Validator::new(preamble);
+    nums.into_iter()
+        .enumerate()
+        .filter(move |&(_, num)| validator.push(num) == Verdict::Invalid)
+}